@@ -0,0 +1,365 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Event schemas shared by the contracts in this repository.
+//!
+//! Every contract emits these types so an indexer sees one topic layout everywhere:
+//! the accounts an event is about (`from`, `to`, `owner`, `spender`, ...) are always
+//! topics, while amounts, counts, indices and text never are. The only other topic is
+//! the memo of `TransferWithMemo`, which exists to be looked up.
+//!
+//! The first topic of each event is its signature topic, the Blake2x256 hash of the
+//! signature below. The remaining topics follow in field order.
+//!
+//! | Event                  | Signature                                                  | Topics                                    |
+//! |------------------------|------------------------------------------------------------|-------------------------------------------|
+//! | `Transfer`             | `Transfer(Option<AccountId>,Option<AccountId>,u128)`       | `from`, `to`                              |
+//! | `TransferWithMemo`     | `TransferWithMemo(AccountId,AccountId,u128,[u8;32])`       | `from`, `to`, `memo`                      |
+//! | `Approval`             | `Approval(AccountId,AccountId,u128)`                       | `owner`, `spender`                        |
+//! | `OwnershipTransferred` | `OwnershipTransferred(AccountId,AccountId)`                | `previous_owner`, `new_owner`             |
+//! | `Paused`               | `Paused(AccountId)`                                        | `account`                                 |
+//! | `Unpaused`             | `Unpaused(AccountId)`                                      | `account`                                 |
+//! | `DelegateChanged`      | `DelegateChanged(AccountId,Option<AccountId>,AccountId)`   | `delegator`, `from_delegate`, `to_delegate` |
+//! | `SupplyChanged`        | `SupplyChanged(u128)`                                      |                                           |
+//! | `EmergencyWithdrawal`  | `EmergencyWithdrawal(AccountId,u128)`                      | `to`                                      |
+//! | `ItemAdded`            | `ItemAdded(AccountId,u32,String)`                          | `owner`                                   |
+//! | `ItemCompleted`        | `ItemCompleted(AccountId,u32)`                             | `owner`                                   |
+//! | `ListCleared`          | `ListCleared(AccountId,u32)`                               | `owner`                                   |
+//! | `TodoCreated`          | `TodoCreated(AccountId,u32)`                               | `owner`                                   |
+//! | `TodoUpdated`          | `TodoUpdated(AccountId,u32)`                               | `owner`                                   |
+//! | `TodoDeleted`          | `TodoDeleted(AccountId,u32)`                               | `owner`                                   |
+
+use ink::prelude::string::String;
+use ink::primitives::AccountId;
+
+/// Event emitted when tokens are moved, minted or burned.
+/// `from` is `None` for newly minted tokens and `to` is `None` for burned ones.
+#[ink::event]
+pub struct Transfer {
+    #[ink(topic)]
+    pub from: Option<AccountId>,
+    #[ink(topic)]
+    pub to: Option<AccountId>,
+    pub value: u128,
+}
+
+/// Event emitted alongside `Transfer` by `transfer_with_memo`.
+/// The memo is an opaque payment reference chosen by the sender.
+#[ink::event]
+pub struct TransferWithMemo {
+    #[ink(topic)]
+    pub from: AccountId,
+    #[ink(topic)]
+    pub to: AccountId,
+    pub value: u128,
+    #[ink(topic)]
+    pub memo: [u8; 32],
+}
+
+/// Event emitted when an allowance changes.
+/// `value` is always the resulting allowance, never the delta.
+#[ink::event]
+pub struct Approval {
+    #[ink(topic)]
+    pub owner: AccountId,
+    #[ink(topic)]
+    pub spender: AccountId,
+    pub value: u128,
+}
+
+/// Event emitted when ownership is transferred or renounced.
+#[ink::event]
+pub struct OwnershipTransferred {
+    #[ink(topic)]
+    pub previous_owner: AccountId,
+    #[ink(topic)]
+    pub new_owner: AccountId,
+}
+
+/// Event emitted when a contract is paused.
+#[ink::event]
+pub struct Paused {
+    #[ink(topic)]
+    pub account: AccountId,
+}
+
+/// Event emitted when a contract is unpaused.
+#[ink::event]
+pub struct Unpaused {
+    #[ink(topic)]
+    pub account: AccountId,
+}
+
+/// Event emitted when an account changes the delegate of its voting power.
+#[ink::event]
+pub struct DelegateChanged {
+    #[ink(topic)]
+    pub delegator: AccountId,
+    #[ink(topic)]
+    pub from_delegate: Option<AccountId>,
+    #[ink(topic)]
+    pub to_delegate: AccountId,
+}
+
+/// Event emitted when the owner expands or contracts the supply through its own balance.
+/// `new_total` is the total supply afterwards; the matching `Transfer` carries the delta.
+#[ink::event]
+pub struct SupplyChanged {
+    pub new_total: u128,
+}
+
+/// Event emitted when the owner moves native value out of a paused contract.
+#[ink::event]
+pub struct EmergencyWithdrawal {
+    #[ink(topic)]
+    pub to: AccountId,
+    pub amount: u128,
+}
+
+/// Event emitted when an item is added to `owner`'s todo list at `index`.
+#[ink::event]
+pub struct ItemAdded {
+    #[ink(topic)]
+    pub owner: AccountId,
+    pub index: u32,
+    pub description: String,
+}
+
+/// Event emitted when the item at `index` of `owner`'s todo list gets completed.
+#[ink::event]
+pub struct ItemCompleted {
+    #[ink(topic)]
+    pub owner: AccountId,
+    pub index: u32,
+}
+
+/// Event emitted when `count` items are removed from `owner`'s todo list at once.
+#[ink::event]
+pub struct ListCleared {
+    #[ink(topic)]
+    pub owner: AccountId,
+    pub count: u32,
+}
+
+/// Event emitted when the item with id `id` is created in `owner`'s todo list.
+#[ink::event]
+pub struct TodoCreated {
+    #[ink(topic)]
+    pub owner: AccountId,
+    pub id: u32,
+}
+
+/// Event emitted when the description, priority or due date of the item with id `id`
+/// in `owner`'s todo list changes.
+#[ink::event]
+pub struct TodoUpdated {
+    #[ink(topic)]
+    pub owner: AccountId,
+    pub id: u32,
+}
+
+/// Event emitted when the item with id `id` is deleted from `owner`'s todo list.
+#[ink::event]
+pub struct TodoDeleted {
+    #[ink(topic)]
+    pub owner: AccountId,
+    pub id: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ink::env::hash::{Blake2x256, CryptoHash};
+    use ink::env::test::EmittedEvent;
+    use ink::env::DefaultEnvironment;
+    use ink::prelude::vec::Vec;
+    use ink::prelude::vec;
+
+    const ALICE: AccountId = AccountId([1u8; 32]);
+    const BOB: AccountId = AccountId([2u8; 32]);
+
+    /// Emits `event` and returns it as recorded by the off-chain environment.
+    fn emit<E: ink::env::Event>(event: E) -> EmittedEvent {
+        ink::env::emit_event::<DefaultEnvironment, E>(event);
+        ink::env::test::recorded_events().last().expect("no event recorded")
+    }
+
+    /// Hashes an event signature the way signature topics are derived.
+    fn signature(signature: &str) -> Vec<u8> {
+        let mut output = [0u8; 32];
+        Blake2x256::hash(signature.as_bytes(), &mut output);
+        output.to_vec()
+    }
+
+    /// Encodes a topic field: values up to 32 bytes are zero padded, longer ones hashed.
+    fn topic<T: scale::Encode>(value: &T) -> Vec<u8> {
+        let encoded = value.encode();
+        let mut output = [0u8; 32];
+        if encoded.len() <= 32 {
+            output[..encoded.len()].copy_from_slice(&encoded);
+        } else {
+            Blake2x256::hash(&encoded, &mut output);
+        }
+        output.to_vec()
+    }
+
+    #[ink::test]
+    fn transfer_topics_are_stable() {
+        let event = emit(Transfer { from: Some(ALICE), to: None, value: 5 });
+        assert_eq!(
+            event.topics,
+            vec![
+                signature("Transfer(Option<AccountId>,Option<AccountId>,u128)"),
+                // Optional topics are the inner value, or all zero for `None`
+                topic(&ALICE),
+                topic(&None::<AccountId>),
+            ]
+        );
+        let decoded = <Transfer as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.value, 5);
+    }
+
+    #[ink::test]
+    fn transfer_with_memo_topics_are_stable() {
+        let memo = [7u8; 32];
+        let event = emit(TransferWithMemo { from: ALICE, to: BOB, value: 5, memo });
+        assert_eq!(
+            event.topics,
+            vec![
+                signature("TransferWithMemo(AccountId,AccountId,u128,[u8;32])"),
+                topic(&ALICE),
+                topic(&BOB),
+                topic(&memo),
+            ]
+        );
+        let decoded = <TransferWithMemo as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.value, 5);
+    }
+
+    #[ink::test]
+    fn approval_topics_are_stable() {
+        let event = emit(Approval { owner: ALICE, spender: BOB, value: 5 });
+        assert_eq!(
+            event.topics,
+            vec![
+                signature("Approval(AccountId,AccountId,u128)"),
+                topic(&ALICE),
+                topic(&BOB),
+            ]
+        );
+        let decoded = <Approval as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.value, 5);
+    }
+
+    #[ink::test]
+    fn ownership_transferred_topics_are_stable() {
+        let event = emit(OwnershipTransferred { previous_owner: ALICE, new_owner: BOB });
+        assert_eq!(
+            event.topics,
+            vec![
+                signature("OwnershipTransferred(AccountId,AccountId)"),
+                topic(&ALICE),
+                topic(&BOB),
+            ]
+        );
+        let decoded = <OwnershipTransferred as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.new_owner, BOB);
+    }
+
+    #[ink::test]
+    fn pause_topics_are_stable() {
+        let event = emit(Paused { account: ALICE });
+        assert_eq!(event.topics, vec![signature("Paused(AccountId)"), topic(&ALICE)]);
+        let event = emit(Unpaused { account: ALICE });
+        assert_eq!(event.topics, vec![signature("Unpaused(AccountId)"), topic(&ALICE)]);
+        let decoded = <Unpaused as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.account, ALICE);
+    }
+
+    #[ink::test]
+    fn delegate_changed_topics_are_stable() {
+        let event = emit(DelegateChanged { delegator: ALICE, from_delegate: None, to_delegate: BOB });
+        assert_eq!(
+            event.topics,
+            vec![
+                signature("DelegateChanged(AccountId,Option<AccountId>,AccountId)"),
+                topic(&ALICE),
+                topic(&None::<AccountId>),
+                topic(&BOB),
+            ]
+        );
+        let decoded = <DelegateChanged as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.to_delegate, BOB);
+    }
+
+    #[ink::test]
+    fn supply_changed_topics_are_stable() {
+        let event = emit(SupplyChanged { new_total: 500 });
+        assert_eq!(event.topics, vec![signature("SupplyChanged(u128)")]);
+        let decoded = <SupplyChanged as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.new_total, 500);
+    }
+
+    #[ink::test]
+    fn emergency_withdrawal_topics_are_stable() {
+        let event = emit(EmergencyWithdrawal { to: BOB, amount: 5 });
+        assert_eq!(
+            event.topics,
+            vec![signature("EmergencyWithdrawal(AccountId,u128)"), topic(&BOB)]
+        );
+        let decoded = <EmergencyWithdrawal as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.amount, 5);
+    }
+
+    #[ink::test]
+    fn todo_list_topics_are_stable() {
+        let event = emit(ItemAdded { owner: ALICE, index: 3, description: "write tests".into() });
+        assert_eq!(
+            event.topics,
+            vec![signature("ItemAdded(AccountId,u32,String)"), topic(&ALICE)]
+        );
+        let decoded = <ItemAdded as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.description, "write tests");
+
+        let event = emit(ItemCompleted { owner: ALICE, index: 3 });
+        assert_eq!(event.topics, vec![signature("ItemCompleted(AccountId,u32)"), topic(&ALICE)]);
+        let decoded = <ItemCompleted as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.index, 3);
+
+        let event = emit(ListCleared { owner: ALICE, count: 2 });
+        assert_eq!(event.topics, vec![signature("ListCleared(AccountId,u32)"), topic(&ALICE)]);
+        let decoded = <ListCleared as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.count, 2);
+    }
+
+    #[ink::test]
+    fn todo_lifecycle_topics_are_stable() {
+        let event = emit(TodoCreated { owner: ALICE, id: 4 });
+        assert_eq!(event.topics, vec![signature("TodoCreated(AccountId,u32)"), topic(&ALICE)]);
+        let decoded = <TodoCreated as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.id, 4);
+
+        let event = emit(TodoUpdated { owner: ALICE, id: 4 });
+        assert_eq!(event.topics, vec![signature("TodoUpdated(AccountId,u32)"), topic(&ALICE)]);
+        let decoded = <TodoUpdated as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.id, 4);
+
+        let event = emit(TodoDeleted { owner: ALICE, id: 4 });
+        assert_eq!(event.topics, vec![signature("TodoDeleted(AccountId,u32)"), topic(&ALICE)]);
+        let decoded = <TodoDeleted as scale::Decode>::decode(&mut &event.data[..])
+            .expect("decoding failed");
+        assert_eq!(decoded.id, 4);
+    }
+}
@@ -0,0 +1,101 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Owner-based access control shared by the contracts in this repository.
+
+use ink::primitives::AccountId;
+
+/// Errors returned by `Ownable` messages and the `only_owner` guard.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum OwnableError {
+    /// Returned if the caller is not the owner.
+    NotOwner,
+    /// Returned if the caller is not the pending owner.
+    NotPendingOwner,
+}
+
+/// A contract with a single owner allowed to perform admin actions.
+#[ink::trait_definition]
+pub trait Ownable {
+    /// Returns the current owner, the zero account once ownership was renounced.
+    #[ink(message)]
+    fn owner(&self) -> AccountId;
+
+    /// Returns the account that was offered ownership and has not accepted it yet.
+    #[ink(message)]
+    fn pending_owner(&self) -> Option<AccountId>;
+
+    /// Offers ownership to `new_owner`, replacing any earlier offer.
+    /// Nothing changes until `new_owner` calls `accept_ownership`.
+    /// Returns an error if the caller is not the owner.
+    #[ink(message)]
+    fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), OwnableError>;
+
+    /// Takes over ownership offered by `transfer_ownership`.
+    /// Returns an error if the caller is not the pending owner.
+    #[ink(message)]
+    fn accept_ownership(&mut self) -> Result<(), OwnableError>;
+
+    /// Withdraws a pending offer made by `transfer_ownership`.
+    /// Returns an error if the caller is not the owner.
+    #[ink(message)]
+    fn cancel_ownership_transfer(&mut self) -> Result<(), OwnableError>;
+
+    /// Gives up ownership by handing it to the zero account.
+    /// Returns an error if the caller is not the owner.
+    #[ink(message)]
+    fn renounce_ownership(&mut self) -> Result<(), OwnableError>;
+}
+
+/// The account holding ownership once it was renounced.
+pub const RENOUNCED: AccountId = AccountId([0u8; 32]);
+
+/// Returns an error unless `caller` is `owner`.
+/// Contracts call this from their admin messages with `self.env().caller()`.
+pub fn only_owner(caller: AccountId, owner: AccountId) -> Result<(), OwnableError> {
+    if caller != owner {
+        return Err(OwnableError::NotOwner);
+    }
+    Ok(())
+}
+
+/// Returns an error unless `caller` is the `pending` owner.
+pub fn only_pending_owner(caller: AccountId, pending: Option<AccountId>) -> Result<(), OwnableError> {
+    if pending != Some(caller) {
+        return Err(OwnableError::NotPendingOwner);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_owner_accepts_owner() {
+        let owner = AccountId::from([1u8; 32]);
+        assert_eq!(only_owner(owner, owner), Ok(()));
+    }
+
+    #[test]
+    fn only_owner_rejects_others() {
+        let owner = AccountId::from([1u8; 32]);
+        let other = AccountId::from([2u8; 32]);
+        assert_eq!(only_owner(other, owner), Err(OwnableError::NotOwner));
+    }
+
+    #[test]
+    fn nobody_owns_after_renouncing() {
+        let caller = AccountId::from([1u8; 32]);
+        assert_eq!(only_owner(caller, RENOUNCED), Err(OwnableError::NotOwner));
+    }
+
+    #[test]
+    fn only_pending_owner_checks_the_offer() {
+        let pending = AccountId::from([1u8; 32]);
+        let other = AccountId::from([2u8; 32]);
+        assert_eq!(only_pending_owner(pending, Some(pending)), Ok(()));
+        assert_eq!(only_pending_owner(other, Some(pending)), Err(OwnableError::NotPendingOwner));
+        assert_eq!(only_pending_owner(pending, None), Err(OwnableError::NotPendingOwner));
+    }
+}
@@ -2,8 +2,34 @@
 
 #[ink::contract]
 mod todo_list {
+    use ink::env::call::{build_call, ExecutionInput, Selector};
     use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+    use events::{ItemAdded, ItemCompleted, ListCleared, TodoCreated, TodoDeleted, TodoUpdated};
+    use ownable::{Ownable, OwnableError};
+    use token::PSP22Error;
+
+    /// Maximum number of tags a single item can carry.
+    pub const MAX_TAGS: usize = 10;
+
+    /// Maximum number of notes a single item can carry.
+    pub const MAX_NOTES: usize = 20;
+
+    /// Maximum number of subtasks a single item can carry.
+    pub const MAX_SUBTASKS: usize = 20;
+
+    /// Default limit on the items a single user can store.
+    pub const DEFAULT_MAX_ITEMS: u32 = 1000;
+
+    /// Default limit on the length of an item description, in bytes.
+    pub const DEFAULT_MAX_DESCRIPTION_LEN: u32 = 256;
+
+    /// Storage layout version this code expects, bumped whenever `migrate` has work to do.
+    pub const STORAGE_VERSION: u16 = 1;
+
+    /// An item in some user's list, as (list owner, item id).
+    pub type ItemRef = (AccountId, u32);
 
     #[derive(scale::Encode, scale::Decode, Default, Clone, Debug, PartialEq, Eq)]
     #[cfg_attr(
@@ -11,92 +37,2852 @@ mod todo_list {
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
     pub struct TodoItem {
+        pub id: u32,
+        pub description: String,
+        /// Not stored with the item but looked up in `TodoList::completed_items`
+        pub completed: bool,
+        pub priority: Priority,
+        pub due_date: Option<Timestamp>,
+        pub created_at: Timestamp,
+        pub completed_at: Option<Timestamp>,
+        pub tags: Vec<String>,
+        pub assignee: Option<AccountId>,
+        pub recurrence: Option<u64>,
+        pub subtasks: Vec<Subtask>,
+        pub archived: bool,
+        pub notes: Vec<String>,
+        /// Native funds awaiting the assignee, held by the contract
+        pub bounty: u128,
+    }
+
+    /// A `TodoItem` as stored in a list or archive. Completion lives in
+    /// `TodoList::completed_items` instead, and getters join the two.
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    struct StoredItem {
+        id: u32,
+        description: String,
+        priority: Priority,
+        due_date: Option<Timestamp>,
+        created_at: Timestamp,
+        completed_at: Option<Timestamp>,
+        tags: Vec<String>,
+        assignee: Option<AccountId>,
+        recurrence: Option<u64>,
+        subtasks: Vec<Subtask>,
+        archived: bool,
+        notes: Vec<String>,
+        bounty: u128,
+    }
+
+    impl StoredItem {
+        fn into_item(self, completed: bool) -> TodoItem {
+            TodoItem {
+                id: self.id,
+                description: self.description,
+                completed,
+                priority: self.priority,
+                due_date: self.due_date,
+                created_at: self.created_at,
+                completed_at: self.completed_at,
+                tags: self.tags,
+                assignee: self.assignee,
+                recurrence: self.recurrence,
+                subtasks: self.subtasks,
+                archived: self.archived,
+                notes: self.notes,
+                bounty: self.bounty,
+            }
+        }
+    }
+
+    impl From<TodoItem> for StoredItem {
+        fn from(item: TodoItem) -> Self {
+            StoredItem {
+                id: item.id,
+                description: item.description,
+                priority: item.priority,
+                due_date: item.due_date,
+                created_at: item.created_at,
+                completed_at: item.completed_at,
+                tags: item.tags,
+                assignee: item.assignee,
+                recurrence: item.recurrence,
+                subtasks: item.subtasks,
+                archived: item.archived,
+                notes: item.notes,
+                bounty: item.bounty,
+            }
+        }
+    }
+
+    /// A checklist entry under a todo. Subtasks get their own small type rather than
+    /// nesting `TodoItem`, which keeps the storage type non-recursive and the
+    /// checklist one level deep.
+    #[derive(scale::Encode, scale::Decode, Default, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Subtask {
         pub description: String,
         pub completed: bool,
     }
 
+    #[derive(scale::Encode, scale::Decode, Default, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Priority {
+        Low,
+        #[default]
+        Medium,
+        High,
+    }
+
+    /// Orders accepted by `get_items_sorted`. Ties keep storage order, and items
+    /// without a due date sort last under both date keys.
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum SortKey {
+        DueDateAsc,
+        DueDateDesc,
+        PriorityDesc,
+        CreatedAtAsc,
+    }
+
+    /// Filters accepted by `get_items_by_status`. Every status but `Archived`
+    /// selects from the active list.
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum ItemStatus {
+        All,
+        Completed,
+        Pending,
+        /// Pending items whose due date has passed, as in `get_overdue_items`.
+        Overdue,
+        Archived,
+    }
+
+    /// The latest undoable change to a list, kept until it is undone or the list
+    /// changes again.
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum UndoRecord {
+        /// `item` was deleted from position `index`.
+        Deleted { index: u32, item: TodoItem },
+        /// `item`, as it was before, got completed. `spawned` is the id of the next
+        /// occurrence created if it recurs.
+        Completed { item: TodoItem, spawned: Option<u32> },
+    }
+
+    /// What a collaborator may do on a list shared with them.
+    /// Lists are public on-chain either way, so `Viewer` only records read access.
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Role {
+        Viewer,
+        /// May add, complete and edit items, but not delete or clear them.
+        Editor,
+    }
+
     #[ink(storage)]
     pub struct TodoList {
-        items: Vec<TodoItem>,
+        lists: Mapping<AccountId, Vec<StoredItem>>,
+        archives: Mapping<AccountId, Vec<StoredItem>>,
+        /// Registry of every list owner by position, in the order they first added an item
+        users: Mapping<u32, AccountId>,
         owner: AccountId,
+        next_id: u32,
+        max_items: u32,
+        storage_version: u16,
+        token: Option<AccountId>,
+        item_fee: u128,
+        /// Roles granted per list, keyed by (list owner, collaborator)
+        collaborators: Mapping<(AccountId, AccountId), Role>,
+        /// Longest description accepted, in UTF-8 bytes
+        max_description_len: u32,
+        /// Block timestamp of the latest change to each user's list or archive
+        last_modified: Mapping<AccountId, Timestamp>,
+        /// Latest undoable change per list, cleared by any other change
+        undo_records: Mapping<AccountId, UndoRecord>,
+        /// Whether adding an item that matches a pending item's description fails
+        reject_duplicates: bool,
+        /// Account offered the admin role that has not accepted it yet
+        pending_owner: Option<AccountId>,
+        /// Number of entries in `users`
+        user_count: u32,
+        /// Ids of the completed items across all lists and archives
+        completed_items: Mapping<u32, ()>,
+        /// Account item fees are paid to, kept when ownership is renounced
+        fee_recipient: AccountId,
+        /// (list owner, item id) of the items assigned to each account, archived ones included
+        assigned_to: Mapping<AccountId, Vec<ItemRef>>,
     }
 
     impl TodoList {
         #[ink(constructor)]
         pub fn new() -> Self {
+            Self::with_max_items(DEFAULT_MAX_ITEMS)
+        }
+
+        /// Limits each user to `max_items` stored items, archived ones included.
+        #[ink(constructor)]
+        pub fn with_max_items(max_items: u32) -> Self {
             Self {
-                items: Vec::new(),
+                lists: Mapping::new(),
+                archives: Mapping::new(),
+                users: Mapping::new(),
                 owner: Self::env().caller(),
+                next_id: 0,
+                max_items,
+                storage_version: STORAGE_VERSION,
+                token: None,
+                item_fee: 0,
+                collaborators: Mapping::new(),
+                max_description_len: DEFAULT_MAX_DESCRIPTION_LEN,
+                last_modified: Mapping::new(),
+                undo_records: Mapping::new(),
+                reject_duplicates: false,
+                pending_owner: None,
+                user_count: 0,
+                completed_items: Mapping::new(),
+                fee_recipient: Self::env().caller(),
+                assigned_to: Mapping::new(),
             }
         }
 
+        /// Charges `item_fee` PSP22 tokens of the `token` contract per added item,
+        /// paid from the caller to the `fee_recipient`, which starts out as the owner
+        /// and follows accepted ownership transfers but not a renounce. Callers must approve this contract
+        /// as a spender on the token first.
+        #[ink(constructor)]
+        pub fn with_item_fee(token: AccountId, item_fee: u128) -> Self {
+            let mut todo_list = Self::new();
+            todo_list.token = Some(token);
+            todo_list.item_fee = item_fee;
+            todo_list
+        }
+
+        /// With `reject_duplicates` set, adding an item whose description exactly matches
+        /// a pending item on the same list fails with `Error::DuplicateItem`. Completed
+        /// items don't count, so finished tasks can be added again.
+        #[ink(constructor)]
+        pub fn with_reject_duplicates(reject_duplicates: bool) -> Self {
+            let mut todo_list = Self::new();
+            todo_list.reject_duplicates = reject_duplicates;
+            todo_list
+        }
+
         #[ink(constructor)]
         pub fn default() -> Self {
             Self::new()
         }
 
+        /// Adds an item with `Medium` priority.
         #[ink(message)]
-        pub fn add_item(&mut self, description: String) {
-            self.items.push(TodoItem {
-                description,
-                completed: false,
-            });
+        pub fn add_item(&mut self, description: String) -> Result<(), Error> {
+            self.add_item_with_priority(description, Priority::Medium)
         }
 
+        #[ink(message)]
+        pub fn add_item_with_priority(&mut self, description: String, priority: Priority) -> Result<(), Error> {
+            self.push_item(self.env().caller(), description, priority, None)
+        }
+
+        /// Adds an item with `Medium` priority that is due at `due_date`.
+        #[ink(message)]
+        pub fn add_item_with_due(&mut self, description: String, due_date: Timestamp) -> Result<(), Error> {
+            self.push_item(self.env().caller(), description, Priority::Medium, Some(due_date))
+        }
+
+        /// Adds an item with `Medium` priority to the list of `list_owner`.
+        /// Returns `Error::NotAuthorized` unless the caller is the owner or an `Editor`.
+        #[ink(message)]
+        pub fn add_item_to(&mut self, list_owner: AccountId, description: String) -> Result<(), Error> {
+            self.ensure_can_edit(list_owner)?;
+            self.push_item(list_owner, description, Priority::Medium, None)
+        }
+
+        /// Appends all `descriptions` as `Medium` priority items, in order.
+        /// Nothing is added if any description is empty or too long. One `ItemAdded` and one
+        /// `TodoCreated` are emitted per item, so indexers handle batches like single adds.
+        #[ink(message)]
+        pub fn add_items(&mut self, descriptions: Vec<String>) -> Result<(), Error> {
+            if descriptions.iter().any(|description| description.is_empty()) {
+                return Err(Error::EmptyDescription);
+            }
+            for (i, description) in descriptions.iter().enumerate() {
+                self.ensure_description_len(description)?;
+                if self.reject_duplicates && descriptions[..i].contains(description) {
+                    return Err(Error::DuplicateItem);
+                }
+            }
+            self.ensure_no_duplicates(self.env().caller(), &descriptions)?;
+            self.ensure_capacity(self.env().caller(), descriptions.len())?;
+            self.charge_fee(descriptions.len() as u32)?;
+            let caller = self.env().caller();
+            let mut stored = self.stored_items_of(caller);
+            for description in descriptions {
+                let item = self.new_item(description, Priority::Medium, None);
+                self.emit_added(caller, stored.len() as u32, &item);
+                stored.push(item.into());
+            }
+            self.save_stored_of(caller, stored);
+            Ok(())
+        }
+
+        /// Returns the caller's own list, without archived items.
         #[ink(message)]
         pub fn get_items(&self) -> Vec<TodoItem> {
-            self.items.clone()
+            self.get_items_of(self.env().caller())
+        }
+
+        /// Returns the list of `owner`, so lists can be shared for viewing.
+        #[ink(message)]
+        pub fn get_items_of(&self, owner: AccountId) -> Vec<TodoItem> {
+            self.join_completion(self.stored_items_of(owner))
+        }
+
+        /// Returns the block timestamp of the latest change to the caller's list, or 0 if
+        /// it never changed, so sync clients can skip fetching an unchanged list.
+        #[ink(message)]
+        pub fn last_modified(&self) -> Timestamp {
+            self.last_modified_of(self.env().caller())
+        }
+
+        /// Returns the block timestamp of the latest change to `owner`'s list, or 0.
+        #[ink(message)]
+        pub fn last_modified_of(&self, owner: AccountId) -> Timestamp {
+            self.last_modified.get(owner).unwrap_or(0)
+        }
+
+        /// Returns the caller's archived items, in the order they were archived.
+        #[ink(message)]
+        pub fn get_archived_items(&self) -> Vec<TodoItem> {
+            self.join_completion(self.archives.get(self.env().caller()).unwrap_or_default())
+        }
+
+        /// Returns the item at `index`, looking up the completion of that item only.
+        #[ink(message)]
+        pub fn get_item(&self, index: u32) -> Option<TodoItem> {
+            let item = self.stored_items_of(self.env().caller()).into_iter().nth(index as usize)?;
+            let completed = self.completed_items.contains(item.id);
+            Some(item.into_item(completed))
+        }
+
+        #[ink(message)]
+        pub fn item_count(&self) -> u32 {
+            self.stored_items_of(self.env().caller()).len() as u32
+        }
+
+        #[ink(message)]
+        pub fn count_completed(&self) -> u32 {
+            self.stored_items_of(self.env().caller())
+                .iter()
+                .filter(|item| self.completed_items.contains(item.id))
+                .count() as u32
+        }
+
+        #[ink(message)]
+        pub fn count_pending(&self) -> u32 {
+            self.item_count() - self.count_completed()
+        }
+
+        /// Returns the share of the caller's items that are completed, from 0 to 100.
+        /// Rounds down, so 1 of 3 is 33 and 100 means every item is done.
+        /// An empty list is 0.
+        #[ink(message)]
+        pub fn completion_percentage(&self) -> u8 {
+            let total = self.item_count();
+            if total == 0 {
+                return 0;
+            }
+            // Widened so large lists can't overflow before dividing
+            (u64::from(self.count_completed()) * 100 / u64::from(total)) as u8
+        }
+
+        /// Returns the longest description accepted, counted in UTF-8 bytes rather than
+        /// characters.
+        #[ink(message)]
+        pub fn max_description_len(&self) -> u32 {
+            self.max_description_len
+        }
+
+        /// Returns how many more items the caller can store before `Error::ListFull`.
+        #[ink(message)]
+        pub fn capacity_remaining(&self) -> u32 {
+            self.capacity_of(self.env().caller())
+        }
+
+        /// Returns up to `limit` items starting at index `start`, for paging with `item_count`.
+        #[ink(message)]
+        pub fn get_items_paged(&self, start: u32, limit: u32) -> Vec<TodoItem> {
+            self.get_items()
+                .into_iter()
+                .skip(start as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn get_completed_items(&self) -> Vec<TodoItem> {
+            self.get_items().into_iter().filter(|item| item.completed).collect()
+        }
+
+        #[ink(message)]
+        pub fn get_pending_items(&self) -> Vec<TodoItem> {
+            self.get_items().into_iter().filter(|item| !item.completed).collect()
+        }
+
+        /// Returns up to `limit` pending items, skipping the first `start` of them.
+        #[ink(message)]
+        pub fn get_pending_items_paged(&self, start: u32, limit: u32) -> Vec<TodoItem> {
+            self.get_items()
+                .into_iter()
+                .filter(|item| !item.completed)
+                .skip(start as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        /// Returns the unfinished items whose due date is before the current block timestamp.
+        #[ink(message)]
+        pub fn get_overdue_items(&self) -> Vec<TodoItem> {
+            let now = self.env().block_timestamp();
+            self.get_items()
+                .into_iter()
+                .filter(|item| !item.completed && item.due_date.is_some_and(|due| due < now))
+                .collect()
         }
 
+        /// Returns the caller's items matching `status`, in list order.
         #[ink(message)]
-        pub fn mark_completed(&mut self, index: u32) {
-            if let Some(item) = self.items.get_mut(index as usize) {
-                item.completed = true;
+        pub fn get_items_by_status(&self, status: ItemStatus) -> Vec<TodoItem> {
+            match status {
+                ItemStatus::All => self.get_items(),
+                ItemStatus::Completed => self.get_completed_items(),
+                ItemStatus::Pending => self.get_pending_items(),
+                ItemStatus::Overdue => self.get_overdue_items(),
+                ItemStatus::Archived => self.get_archived_items(),
             }
         }
+
+        /// Returns the caller's items in the order given by `by`. Sorting happens on a
+        /// copy, so the stored order and item indices are unchanged.
+        #[ink(message)]
+        pub fn get_items_sorted(&self, by: SortKey) -> Vec<TodoItem> {
+            let mut items = self.get_items();
+            match by {
+                SortKey::DueDateAsc => items.sort_by_key(|item| (item.due_date.is_none(), item.due_date)),
+                SortKey::DueDateDesc => {
+                    items.sort_by_key(|item| (item.due_date.is_none(), core::cmp::Reverse(item.due_date)))
+                }
+                SortKey::PriorityDesc => items.sort_by_key(|item| core::cmp::Reverse(item.priority)),
+                SortKey::CreatedAtAsc => items.sort_by_key(|item| item.created_at),
+            }
+            items
+        }
+
+        /// Returns the unfinished items due between now and `window_ms` from now.
+        /// Items already overdue are left to `get_overdue_items`.
+        #[ink(message)]
+        pub fn get_due_within(&self, window_ms: u64) -> Vec<TodoItem> {
+            let now = self.env().block_timestamp();
+            let end = now.saturating_add(window_ms);
+            self.get_items()
+                .into_iter()
+                .filter(|item| !item.completed && item.due_date.is_some_and(|due| now <= due && due <= end))
+                .collect()
+        }
+
+        /// Returns the items assigned to `account` across every user's list, in the order
+        /// they were assigned. Only the lists holding such an item are read.
+        #[ink(message)]
+        pub fn get_items_assigned_to(&self, account: AccountId) -> Vec<TodoItem> {
+            self.assigned_to.get(account)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(owner, id)| {
+                    // Archived items stay indexed but aren't part of the list
+                    let item = self.stored_items_of(owner).into_iter().find(|item| item.id == id)?;
+                    let completed = self.completed_items.contains(id);
+                    Some(item.into_item(completed))
+                })
+                .collect()
+        }
+
+        /// Returns the items whose description contains `query`, ignoring case.
+        /// `str::to_lowercase` comes from `alloc`, so this works without `std`.
+        #[ink(message)]
+        pub fn search(&self, query: String) -> Vec<TodoItem> {
+            let query = query.to_lowercase();
+            self.get_items()
+                .into_iter()
+                .filter(|item| item.description.to_lowercase().contains(&query))
+                .collect()
+        }
+
+        /// Returns the index and item of the first item whose description is exactly
+        /// `description`. Unlike `search`, this is case-sensitive and matches whole text.
+        #[ink(message)]
+        pub fn find_item(&self, description: String) -> Option<(u32, TodoItem)> {
+            self.get_items()
+                .into_iter()
+                .enumerate()
+                .find(|(_, item)| item.description == description)
+                .map(|(index, item)| (index as u32, item))
+        }
+
+        #[ink(message)]
+        pub fn get_items_by_tag(&self, tag: String) -> Vec<TodoItem> {
+            self.get_items()
+                .into_iter()
+                .filter(|item| item.tags.contains(&tag))
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn get_items_by_priority(&self, priority: Priority) -> Vec<TodoItem> {
+            self.get_items()
+                .into_iter()
+                .filter(|item| item.priority == priority)
+                .collect()
+        }
+
+        /// Completes the item with id `id`. A recurring item is followed by a fresh
+        /// pending copy appended to the list, due one interval later.
+        /// Emits `ItemCompleted` only if the item wasn't completed already.
+        /// Returns `Error::ItemNotFound` for an unknown id, which used to be
+        /// silently ignored.
+        #[ink(message)]
+        pub fn mark_completed(&mut self, id: u32) -> Result<(), Error> {
+            self.complete_item(self.env().caller(), id)
+        }
+
+        /// Completes the item with id `id` in the list of `list_owner`, as `mark_completed`.
+        /// Returns `Error::NotAuthorized` unless the caller is the owner or an `Editor`.
+        #[ink(message)]
+        pub fn mark_completed_in(&mut self, list_owner: AccountId, id: u32) -> Result<(), Error> {
+            self.ensure_can_edit(list_owner)?;
+            self.complete_item(list_owner, id)
+        }
+
+        /// Completes the items at positions `indices`, as `mark_completed` would.
+        /// Every index is validated first, so either all are completed or none are.
+        #[ink(message)]
+        pub fn mark_many_completed(&mut self, indices: Vec<u32>) -> Result<(), Error> {
+            let items = self.get_items();
+            let mut ids: Vec<u32> = Vec::new();
+            let mut regenerated = 0;
+            for index in indices {
+                let item = items.get(index as usize).ok_or(Error::IndexOutOfBounds)?;
+                if ids.contains(&item.id) {
+                    continue;
+                }
+                if !item.completed && item.recurrence.is_some() {
+                    regenerated += 1;
+                }
+                ids.push(item.id);
+            }
+            // Recurring items add a copy each, which must fit before anything changes
+            self.ensure_capacity(self.env().caller(), regenerated)?;
+
+            for id in ids {
+                self.mark_completed(id)?;
+            }
+            Ok(())
+        }
+
+        /// Flips the completion state of the item at `index`, reopening completed items.
+        /// Emits `ItemCompleted` only when the item becomes completed.
+        #[ink(message)]
+        pub fn toggle_completed(&mut self, index: u32) -> Result<(), Error> {
+            let mut items = self.get_items();
+            let item = items.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            item.completed = !item.completed;
+            item.completed_at = if item.completed {
+                Some(self.env().block_timestamp())
+            } else {
+                None
+            };
+            let completed = item.completed;
+            let id = item.id;
+            self.save_items(items);
+            self.set_completed(id, completed);
+            if completed {
+                self.emit_completed(self.env().caller(), index);
+            }
+            Ok(())
+        }
         
+        /// Removes the caller's completed items, except those with an unclaimed bounty.
         #[ink(message)]
         pub fn clear_completed(&mut self) {
-            self.items.retain(|item| !item.completed);
+            let (items, removed): (Vec<_>, Vec<_>) = self.get_items()
+                .into_iter()
+                .partition(|item| !item.completed || item.bounty > 0);
+            self.forget_items(&removed);
+            self.save_items(items);
         }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+        /// Removes every item from the caller's list, completed or not, except those
+        /// with an unclaimed bounty.
+        #[ink(message)]
+        pub fn clear_all(&mut self) {
+            let (items, removed): (Vec<_>, Vec<_>) = self.get_items()
+                .into_iter()
+                .partition(|item| item.bounty > 0);
+            let count = removed.len() as u32;
+            self.forget_items(&removed);
+            self.save_items(items);
+            self.env().emit_event(ListCleared {
+                owner: self.env().caller(),
+                count,
+            });
+        }
 
-        #[ink::test]
-        fn default_works() {
-            let todo_list = TodoList::default();
-            assert_eq!(todo_list.get_items().len(), 0);
+        /// Removes the item with id `id`. Items after it shift down by one position,
+        /// but their ids don't change.
+        #[ink(message)]
+        pub fn delete_item(&mut self, id: u32) -> Result<(), Error> {
+            self.delete_item_of(self.env().caller(), id)
         }
 
-        #[ink::test]
-        fn add_item_works() {
-            let mut todo_list = TodoList::new();
-            todo_list.add_item("write tests".into());
-            assert_eq!(todo_list.get_items().len(), 1);
-            assert_eq!(todo_list.get_items()[0].description, "write tests");
-            assert!(!todo_list.get_items()[0].completed);
+        /// Reverses the latest delete or completion on the caller's list, including one
+        /// made by a collaborator. A deleted item returns to its original position. Any
+        /// other change to the list, including the undo itself, discards what could be undone.
+        /// Bounties are not part of it: an undone item keeps its current bounty.
+        /// Returns `Error::NothingToUndo` if there is nothing to reverse and
+        /// `Error::BountyUnclaimed` if it would remove a funded recurrence.
+        #[ink(message)]
+        pub fn undo(&mut self) -> Result<(), Error> {
+            let list = self.env().caller();
+            let record = self.undo_records.get(list).ok_or(Error::NothingToUndo)?;
+            let mut items = self.get_items_of(list);
+            match record {
+                UndoRecord::Deleted { index, item } => {
+                    let index = (index as usize).min(items.len());
+                    self.emit_added(list, index as u32, &item);
+                    self.set_completed(item.id, item.completed);
+                    self.add_assignment(item.assignee, list, item.id);
+                    items.insert(index, item);
+                }
+                UndoRecord::Completed { mut item, spawned } => {
+                    if let Some(spawned) = spawned {
+                        if items.iter().any(|existing| existing.id == spawned && existing.bounty > 0) {
+                            return Err(Error::BountyUnclaimed);
+                        }
+                        if let Some(position) = items.iter().position(|existing| existing.id == spawned) {
+                            let removed = items.remove(position);
+                            self.remove_assignment(removed.assignee, list, spawned);
+                        }
+                        self.env().emit_event(TodoDeleted { owner: list, id: spawned });
+                    }
+                    let index = Self::position_of(&items, item.id)?;
+                    self.emit_updated(list, item.id);
+                    self.set_completed(item.id, item.completed);
+                    // Bounties are funded and claimed without discarding the record, so
+                    // the current bounty is kept rather than the one recorded
+                    item.bounty = items[index].bounty;
+                    items[index] = item;
+                }
+            }
+            self.save_items_of(list, items);
+            Ok(())
         }
 
-        #[ink::test]
-        fn mark_completed_works() {
-            let mut todo_list = TodoList::new();
-            todo_list.add_item("write tests".into());
-            todo_list.mark_completed(0);
-            assert!(todo_list.get_items()[0].completed);
+        /// Removes the item with id `id` from the list of `list_owner`, as `delete_item`.
+        /// Only the owner may delete, so collaborators get `Error::NotAuthorized`.
+        #[ink(message)]
+        pub fn delete_item_in(&mut self, list_owner: AccountId, id: u32) -> Result<(), Error> {
+            if self.env().caller() != list_owner {
+                return Err(Error::NotAuthorized);
+            }
+            self.delete_item_of(list_owner, id)
         }
 
-        #[ink::test]
-        fn clear_completed_works() {
-            let mut todo_list = TodoList::new();
-            todo_list.add_item("write tests".into());
-            todo_list.add_item("deploy contract".into());
-            todo_list.mark_completed(0);
-            todo_list.clear_completed();
-            assert_eq!(todo_list.get_items().len(), 1);
-            assert_eq!(todo_list.get_items()[0].description, "deploy contract");
+        /// Moves the item at `index` out of the active list into the archive.
+        /// Archived items are kept apart, so the indices of the active list stay dense.
+        #[ink(message)]
+        pub fn archive_item(&mut self, index: u32) -> Result<(), Error> {
+            let index = index as usize;
+            let mut items = self.get_items();
+            if index >= items.len() {
+                return Err(Error::IndexOutOfBounds);
+            }
+            let mut item = items.remove(index);
+            item.archived = true;
+            let mut archived = self.get_archived_items();
+            archived.push(item);
+            self.save_items(items);
+            self.save_archive(archived);
+            Ok(())
+        }
+
+        /// Moves the archived item at `index` of `get_archived_items` back to the end
+        /// of the active list.
+        #[ink(message)]
+        pub fn unarchive_item(&mut self, index: u32) -> Result<(), Error> {
+            let index = index as usize;
+            let mut archived = self.get_archived_items();
+            if index >= archived.len() {
+                return Err(Error::IndexOutOfBounds);
+            }
+            let mut item = archived.remove(index);
+            item.archived = false;
+            let mut items = self.get_items();
+            items.push(item);
+            self.save_items(items);
+            self.save_archive(archived);
+            Ok(())
+        }
+
+        /// Moves the item at `from` to position `to`, shifting the items in between.
+        /// A `to` past the end moves the item to the end.
+        #[ink(message)]
+        pub fn move_item(&mut self, from: u32, to: u32) -> Result<(), Error> {
+            let from = from as usize;
+            let mut items = self.get_items();
+            if from >= items.len() {
+                return Err(Error::IndexOutOfBounds);
+            }
+            let item = items.remove(from);
+            let to = (to as usize).min(items.len());
+            items.insert(to, item);
+            self.save_items(items);
+            Ok(())
+        }
+
+        /// Replaces the description of the item with id `id`, keeping its completion state.
+        #[ink(message)]
+        pub fn edit_item(&mut self, id: u32, new_description: String) -> Result<(), Error> {
+            self.edit_item_of(self.env().caller(), id, new_description)
+        }
+
+        /// Replaces the description of the item with id `id` in the list of `list_owner`.
+        /// Returns `Error::NotAuthorized` unless the caller is the owner or an `Editor`.
+        #[ink(message)]
+        pub fn edit_item_in(&mut self, list_owner: AccountId, id: u32, new_description: String) -> Result<(), Error> {
+            self.ensure_can_edit(list_owner)?;
+            self.edit_item_of(list_owner, id, new_description)
+        }
+
+        /// Sets or, with `None`, removes the due date of the item at `index`.
+        #[ink(message)]
+        pub fn set_due_date(&mut self, index: u32, due_date: Option<Timestamp>) -> Result<(), Error> {
+            let mut items = self.get_items();
+            let item = items.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            item.due_date = due_date;
+            let id = item.id;
+            self.save_items(items);
+            self.emit_updated(self.env().caller(), id);
+            Ok(())
+        }
+
+        /// Appends a pending subtask to the item at `parent_index`. Subtask descriptions
+        /// follow the same rules as item descriptions.
+        /// Returns `Error::TooManySubtasks` once the item carries `MAX_SUBTASKS` subtasks.
+        #[ink(message)]
+        pub fn add_subtask(&mut self, parent_index: u32, description: String) -> Result<(), Error> {
+            self.ensure_valid_description(&description)?;
+            let mut items = self.get_items();
+            let parent = items.get_mut(parent_index as usize).ok_or(Error::IndexOutOfBounds)?;
+            if parent.subtasks.len() >= MAX_SUBTASKS {
+                return Err(Error::TooManySubtasks);
+            }
+            parent.subtasks.push(Subtask {
+                description,
+                completed: false,
+            });
+            self.save_items(items);
+            Ok(())
+        }
+
+        /// Completes subtask `sub_index` of the item at `parent_index`. Once every
+        /// subtask is done the parent is completed too, as if by `mark_completed`.
+        #[ink(message)]
+        pub fn complete_subtask(&mut self, parent_index: u32, sub_index: u32) -> Result<(), Error> {
+            let mut items = self.get_items();
+            let parent = items.get_mut(parent_index as usize).ok_or(Error::IndexOutOfBounds)?;
+            let subtask = parent.subtasks.get_mut(sub_index as usize).ok_or(Error::IndexOutOfBounds)?;
+            subtask.completed = true;
+            let parent_id = parent.id;
+            let all_done = parent.subtasks.iter().all(|subtask| subtask.completed);
+            self.save_items(items);
+            if all_done {
+                self.mark_completed(parent_id)?;
+            }
+            Ok(())
+        }
+
+        /// Makes the item at `index` recur every `interval` milliseconds, or stop with `None`.
+        #[ink(message)]
+        pub fn set_recurrence(&mut self, index: u32, interval: Option<u64>) -> Result<(), Error> {
+            let mut items = self.get_items();
+            let item = items.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            item.recurrence = interval;
+            self.save_items(items);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn set_priority(&mut self, index: u32, priority: Priority) -> Result<(), Error> {
+            let mut items = self.get_items();
+            let item = items.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            item.priority = priority;
+            let id = item.id;
+            self.save_items(items);
+            self.emit_updated(self.env().caller(), id);
+            Ok(())
+        }
+
+        /// Assigns the item at `index` to `assignee`, or unassigns it with `None`.
+        /// The item isn't copied: it stays in the caller's list and only records the assignee.
+        #[ink(message)]
+        pub fn assign(&mut self, index: u32, assignee: Option<AccountId>) -> Result<(), Error> {
+            let list = self.env().caller();
+            let mut items = self.get_items();
+            let item = items.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            let (id, previous) = (item.id, item.assignee);
+            item.assignee = assignee;
+            self.save_items(items);
+            self.remove_assignment(previous, list, id);
+            self.add_assignment(assignee, list, id);
+            Ok(())
+        }
+
+        /// Adds the transferred value to the bounty of the item at `index` in the list of
+        /// `list_owner`. Anyone may fund an item, and funded items can't be deleted until
+        /// the bounty is claimed.
+        /// Returns `Error::ZeroBounty` if nothing is transferred and `Error::Overflow` if
+        /// the bounty would exceed `u128::MAX`.
+        #[ink(message, payable)]
+        pub fn fund_item(&mut self, list_owner: AccountId, index: u32) -> Result<(), Error> {
+            let value = self.env().transferred_value();
+            if value == 0 {
+                return Err(Error::ZeroBounty);
+            }
+            let mut stored = self.stored_items_of(list_owner);
+            let item = stored.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            item.bounty = item.bounty.checked_add(value).ok_or(Error::Overflow)?;
+            self.save_bounty_of(list_owner, stored);
+            Ok(())
+        }
+
+        /// Pays the bounty of the completed item at `index` in the list of `list_owner`
+        /// to the caller, who must be its assignee.
+        /// Returns `Error::NotAuthorized` for anyone else and `Error::NotCompleted` while
+        /// the item is still pending.
+        #[ink(message)]
+        pub fn claim_bounty(&mut self, list_owner: AccountId, index: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let mut stored = self.stored_items_of(list_owner);
+            let item = stored.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            if item.assignee != Some(caller) {
+                return Err(Error::NotAuthorized);
+            }
+            if !self.completed_items.contains(item.id) {
+                return Err(Error::NotCompleted);
+            }
+            let bounty = core::mem::take(&mut item.bounty);
+            if bounty == 0 {
+                return Ok(());
+            }
+            self.save_bounty_of(list_owner, stored);
+            self.env().transfer(caller, bounty).map_err(|_| Error::TransferFailed)
+        }
+
+        /// Adds `tag` to the item at `index`; tagging an item twice has no effect.
+        /// Returns `Error::TooManyTags` once the item carries `MAX_TAGS` tags.
+        #[ink(message)]
+        pub fn add_tag(&mut self, index: u32, tag: String) -> Result<(), Error> {
+            let mut items = self.get_items();
+            let item = items.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            if item.tags.contains(&tag) {
+                return Ok(());
+            }
+            if item.tags.len() >= MAX_TAGS {
+                return Err(Error::TooManyTags);
+            }
+            item.tags.push(tag);
+            self.save_items(items);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn remove_tag(&mut self, index: u32, tag: String) -> Result<(), Error> {
+            let mut items = self.get_items();
+            let item = items.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            item.tags.retain(|existing| *existing != tag);
+            self.save_items(items);
+            Ok(())
+        }
+
+        /// Appends `note` to the item at `index`.
+        /// Returns `Error::TooManyNotes` once the item carries `MAX_NOTES` notes.
+        #[ink(message)]
+        pub fn add_note(&mut self, index: u32, note: String) -> Result<(), Error> {
+            let mut items = self.get_items();
+            let item = items.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+            if item.notes.len() >= MAX_NOTES {
+                return Err(Error::TooManyNotes);
+            }
+            item.notes.push(note);
+            self.save_items(items);
+            Ok(())
+        }
+
+        /// Returns the notes on the item at `index`, oldest first, or nothing if
+        /// there is no such item.
+        #[ink(message)]
+        pub fn get_notes(&self, index: u32) -> Vec<String> {
+            self.get_item(index).map(|item| item.notes).unwrap_or_default()
+        }
+
+        /// Returns the token contract fees are paid in, if adding items costs a fee.
+        #[ink(message)]
+        pub fn fee_token(&self) -> Option<AccountId> {
+            self.token
+        }
+
+        #[ink(message)]
+        pub fn item_fee(&self) -> u128 {
+            self.item_fee
+        }
+
+        /// Returns the account item fees are paid to.
+        #[ink(message)]
+        pub fn fee_recipient(&self) -> AccountId {
+            self.fee_recipient
+        }
+
+        /// Shares the caller's list with `who` as `role`, replacing any earlier role.
+        #[ink(message)]
+        pub fn add_collaborator(&mut self, who: AccountId, role: Role) {
+            self.collaborators.insert((self.env().caller(), who), &role);
+        }
+
+        #[ink(message)]
+        pub fn remove_collaborator(&mut self, who: AccountId) {
+            self.collaborators.remove((self.env().caller(), who));
+        }
+
+        /// Returns the role of `who` on the list of `list_owner`, if it is shared with them.
+        #[ink(message)]
+        pub fn role_of(&self, list_owner: AccountId, who: AccountId) -> Option<Role> {
+            self.collaborators.get((list_owner, who))
+        }
+
+        #[ink(message)]
+        pub fn version(&self) -> u16 {
+            self.storage_version
+        }
+
+        /// Brings storage written by older code up to `STORAGE_VERSION`. There is
+        /// nothing to convert yet; future upgrades add their steps here.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if self.storage_version >= STORAGE_VERSION {
+                return Err(Error::AlreadyMigrated);
+            }
+            self.storage_version = STORAGE_VERSION;
+            Ok(())
+        }
+
+        /// Deletes every user's list, except items with an unclaimed bounty, which stay
+        /// claimable. Only the admin `owner` may do this.
+        #[ink(message)]
+        pub fn wipe_all(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let now = self.env().block_timestamp();
+            let mut kept = 0;
+            for position in 0..self.user_count {
+                let Some(user) = self.users.get(position) else {
+                    continue;
+                };
+                let (items, removed): (Vec<_>, Vec<_>) = self.lists.get(user)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(self.archives.get(user).unwrap_or_default())
+                    .partition(|item| item.bounty > 0);
+                for item in removed {
+                    self.completed_items.remove(item.id);
+                    self.remove_assignment(item.assignee, user, item.id);
+                }
+                let (archived, items): (Vec<_>, Vec<_>) = items.into_iter().partition(|item| item.archived);
+                if items.is_empty() && archived.is_empty() {
+                    self.lists.remove(user);
+                    self.archives.remove(user);
+                } else {
+                    self.lists.insert(user, &items);
+                    self.archives.insert(user, &archived);
+                    self.users.insert(kept, &user);
+                    kept += 1;
+                }
+                self.last_modified.insert(user, &now);
+                self.undo_records.remove(user);
+            }
+            for position in kept..self.user_count {
+                self.users.remove(position);
+            }
+            self.user_count = kept;
+            Ok(())
+        }
+
+        /// Returns every user's list, in the order users first added an item.
+        /// Only the admin `owner` may do this; prefer `export_users` once the
+        /// registry grows large.
+        #[ink(message)]
+        pub fn export_all(&self) -> Result<Vec<(AccountId, Vec<TodoItem>)>, Error> {
+            self.export_users(0, u32::MAX)
+        }
+
+        /// Returns up to `limit` users' lists starting at registry index `start`.
+        /// Only the admin `owner` may do this.
+        #[ink(message)]
+        pub fn export_users(&self, start: u32, limit: u32) -> Result<Vec<(AccountId, Vec<TodoItem>)>, Error> {
+            self.ensure_owner()?;
+            Ok(self
+                .registered_users(start, limit)
+                .map(|user| (user, self.get_items_of(user)))
+                .collect())
+        }
+
+        /// Returns the number of users in the registry, for paging through `export_users`.
+        #[ink(message)]
+        pub fn user_count(&self) -> u32 {
+            self.user_count
+        }
+
+        /// Reads up to `limit` registry entries starting at index `start`.
+        fn registered_users(&self, start: u32, limit: u32) -> impl Iterator<Item = AccountId> + '_ {
+            let end = start.saturating_add(limit).min(self.user_count);
+            (start..end).filter_map(|position| self.users.get(position))
+        }
+
+        /// Lists are keyed by caller, so item mutations only ever touch the caller's
+        /// own list. Admin messages are additionally gated on `owner`.
+        fn ensure_owner(&self) -> Result<(), Error> {
+            Ok(ownable::only_owner(self.env().caller(), self.owner)?)
+        }
+
+        fn push_item(
+            &mut self,
+            list: AccountId,
+            description: String,
+            priority: Priority,
+            due_date: Option<Timestamp>,
+        ) -> Result<(), Error> {
+            self.ensure_description_len(&description)?;
+            self.ensure_no_duplicates(list, core::slice::from_ref(&description))?;
+            self.ensure_capacity(list, 1)?;
+            self.charge_fee(1)?;
+            let mut stored = self.stored_items_of(list);
+            let item = self.new_item(description, priority, due_date);
+            self.emit_added(list, stored.len() as u32, &item);
+            stored.push(item.into());
+            self.save_stored_of(list, stored);
+            Ok(())
+        }
+
+        fn complete_item(&mut self, list: AccountId, id: u32) -> Result<(), Error> {
+            let mut items = self.get_items_of(list);
+            let index = Self::position_of(&items, id)?;
+            if items[index].completed {
+                return Ok(());
+            }
+            if items[index].recurrence.is_some() {
+                self.ensure_capacity(list, 1)?;
+            }
+            let now = self.env().block_timestamp();
+            let before = items[index].clone();
+            let item = &mut items[index];
+            item.completed = true;
+            item.completed_at = Some(now);
+
+            let done = item.clone();
+            let next = match done.recurrence {
+                Some(interval) => {
+                    // Items without a due date are next due one interval from now
+                    let due_date = done.due_date.unwrap_or(now).saturating_add(interval);
+                    let mut next = self.new_item(done.description, done.priority, Some(due_date));
+                    next.tags = done.tags;
+                    next.assignee = done.assignee;
+                    next.recurrence = done.recurrence;
+                    Some(next)
+                }
+                None => None,
+            };
+            self.set_completed(done.id, true);
+            self.emit_completed(list, index as u32);
+            let spawned = next.as_ref().map(|next| next.id);
+            if let Some(next) = next {
+                self.emit_added(list, items.len() as u32, &next);
+                self.add_assignment(next.assignee, list, next.id);
+                items.push(next);
+            }
+            self.save_items_of(list, items);
+            self.undo_records.insert(list, &UndoRecord::Completed { item: before, spawned });
+            Ok(())
+        }
+
+        fn edit_item_of(&mut self, list: AccountId, id: u32, new_description: String) -> Result<(), Error> {
+            self.ensure_valid_description(&new_description)?;
+            let mut items = self.get_items_of(list);
+            let index = Self::position_of(&items, id)?;
+            items[index].description = new_description;
+            self.save_items_of(list, items);
+            self.emit_updated(list, id);
+            Ok(())
+        }
+
+        fn delete_item_of(&mut self, list: AccountId, id: u32) -> Result<(), Error> {
+            let mut items = self.get_items_of(list);
+            let index = Self::position_of(&items, id)?;
+            if items[index].bounty > 0 {
+                return Err(Error::BountyUnclaimed);
+            }
+            let item = items.remove(index);
+            self.save_items_of(list, items);
+            self.completed_items.remove(id);
+            self.remove_assignment(item.assignee, list, id);
+            self.undo_records.insert(list, &UndoRecord::Deleted { index: index as u32, item });
+            self.env().emit_event(TodoDeleted { owner: list, id });
+            Ok(())
+        }
+
+        /// Returns an error unless the caller owns `list` or is an `Editor` on it.
+        fn ensure_can_edit(&self, list: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if caller != list && self.role_of(list, caller) != Some(Role::Editor) {
+                return Err(Error::NotAuthorized);
+            }
+            Ok(())
+        }
+
+        /// Pulls the fee for `count` items from the caller with `PSP22::transfer_from`.
+        /// A failed payment returns an error, which reverts the whole call.
+        fn charge_fee(&self, count: u32) -> Result<(), Error> {
+            let Some(token) = self.token else {
+                return Ok(());
+            };
+            let fee = self.item_fee.checked_mul(count.into()).ok_or(Error::FeePaymentFailed)?;
+            if fee == 0 {
+                return Ok(());
+            }
+            let result = build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer_from")))
+                        .push_arg(self.env().caller())
+                        .push_arg(self.fee_recipient)
+                        .push_arg(fee)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(Error::FeePaymentFailed),
+            }
+        }
+
+        /// Returns `Error::EmptyDescription` or `Error::DescriptionTooLong` unless `description`
+        /// is fit to replace or sit next to item descriptions.
+        fn ensure_valid_description(&self, description: &str) -> Result<(), Error> {
+            if description.is_empty() {
+                return Err(Error::EmptyDescription);
+            }
+            self.ensure_description_len(description)
+        }
+
+        /// Lengths are counted in UTF-8 bytes, which is what storage and the return
+        /// buffer pay for, so multi-byte characters count more than once.
+        fn ensure_description_len(&self, description: &str) -> Result<(), Error> {
+            if description.len() > self.max_description_len as usize {
+                return Err(Error::DescriptionTooLong);
+            }
+            Ok(())
+        }
+
+        /// Returns `Error::DuplicateItem` if duplicates are rejected and a pending item on
+        /// `list` already has one of `descriptions`.
+        fn ensure_no_duplicates(&self, list: AccountId, descriptions: &[String]) -> Result<(), Error> {
+            if !self.reject_duplicates {
+                return Ok(());
+            }
+            // Completion is only looked up for items with a matching description
+            let duplicate = self.stored_items_of(list)
+                .iter()
+                .any(|item| descriptions.contains(&item.description) && !self.completed_items.contains(item.id));
+            if duplicate {
+                return Err(Error::DuplicateItem);
+            }
+            Ok(())
+        }
+
+        fn ensure_capacity(&self, list: AccountId, additional: usize) -> Result<(), Error> {
+            if additional > self.capacity_of(list) as usize {
+                return Err(Error::ListFull);
+            }
+            Ok(())
+        }
+
+        fn capacity_of(&self, list: AccountId) -> u32 {
+            let stored = self.stored_items_of(list).len() + self.archives.get(list).unwrap_or_default().len();
+            self.max_items.saturating_sub(stored as u32)
+        }
+
+        /// Builds a fresh item with the next unused id.
+        fn new_item(&mut self, description: String, priority: Priority, due_date: Option<Timestamp>) -> TodoItem {
+            let id = self.next_id;
+            self.next_id = self.next_id.checked_add(1).expect("Item id overflow");
+            TodoItem {
+                id,
+                description,
+                completed: false,
+                priority,
+                due_date,
+                created_at: self.env().block_timestamp(),
+                completed_at: None,
+                tags: Vec::new(),
+                assignee: None,
+                recurrence: None,
+                subtasks: Vec::new(),
+                archived: false,
+                notes: Vec::new(),
+                bounty: 0,
+            }
+        }
+
+        fn position_of(items: &[TodoItem], id: u32) -> Result<usize, Error> {
+            items.iter().position(|item| item.id == id).ok_or(Error::ItemNotFound)
+        }
+
+        fn emit_added(&self, owner: AccountId, index: u32, item: &TodoItem) {
+            self.env().emit_event(ItemAdded {
+                owner,
+                index,
+                description: item.description.clone(),
+            });
+            self.env().emit_event(TodoCreated { owner, id: item.id });
+        }
+
+        fn emit_updated(&self, owner: AccountId, id: u32) {
+            self.env().emit_event(TodoUpdated { owner, id });
+        }
+
+        fn emit_completed(&self, owner: AccountId, index: u32) {
+            self.env().emit_event(ItemCompleted { owner, index });
+        }
+
+        fn save_items(&mut self, items: Vec<TodoItem>) {
+            self.save_items_of(self.env().caller(), items);
+        }
+
+        fn save_items_of(&mut self, list: AccountId, items: Vec<TodoItem>) {
+            self.save_stored_of(list, items.into_iter().map(StoredItem::from).collect());
+        }
+
+        /// Saves items that are already in their stored form, leaving completion untouched.
+        fn save_stored_of(&mut self, list: AccountId, stored: Vec<StoredItem>) {
+            if !self.lists.contains(list) {
+                self.users.insert(self.user_count, &list);
+                self.user_count += 1;
+            }
+            self.lists.insert(list, &stored);
+            self.last_modified.insert(list, &self.env().block_timestamp());
+            self.undo_records.remove(list);
+        }
+
+        /// Saves a list whose only change is a bounty. Bounties don't count as edits of
+        /// the list, so `last_modified` and the undo record are left alone.
+        fn save_bounty_of(&mut self, list: AccountId, stored: Vec<StoredItem>) {
+            self.lists.insert(list, &stored);
+        }
+
+        fn save_archive(&mut self, archived: Vec<TodoItem>) {
+            let stored: Vec<StoredItem> = archived.into_iter().map(StoredItem::from).collect();
+            self.archives.insert(self.env().caller(), &stored);
+        }
+
+        /// The `completed` flag of saved items is dropped, so every message that completes
+        /// or reopens an item records it here.
+        fn set_completed(&mut self, id: u32, completed: bool) {
+            if completed {
+                self.completed_items.insert(id, &());
+            } else {
+                self.completed_items.remove(id);
+            }
+        }
+
+        /// Drops the completion state and assignment of the caller's items removed for good.
+        fn forget_items(&mut self, removed: &[TodoItem]) {
+            let list = self.env().caller();
+            for item in removed {
+                self.completed_items.remove(item.id);
+                self.remove_assignment(item.assignee, list, item.id);
+            }
+        }
+
+        /// Indexes item `id` on `list` under `assignee`, if it has one.
+        fn add_assignment(&mut self, assignee: Option<AccountId>, list: AccountId, id: u32) {
+            let Some(assignee) = assignee else {
+                return;
+            };
+            let mut assigned = self.assigned_to.get(assignee).unwrap_or_default();
+            if !assigned.contains(&(list, id)) {
+                assigned.push((list, id));
+                self.assigned_to.insert(assignee, &assigned);
+            }
+        }
+
+        /// Drops item `id` on `list` from the index of `assignee`, if it has one.
+        fn remove_assignment(&mut self, assignee: Option<AccountId>, list: AccountId, id: u32) {
+            let Some(assignee) = assignee else {
+                return;
+            };
+            let mut assigned = self.assigned_to.get(assignee).unwrap_or_default();
+            assigned.retain(|entry| *entry != (list, id));
+            if assigned.is_empty() {
+                self.assigned_to.remove(assignee);
+            } else {
+                self.assigned_to.insert(assignee, &assigned);
+            }
+        }
+
+        /// Reads `list` without looking up completion, for callers that don't need it.
+        fn stored_items_of(&self, list: AccountId) -> Vec<StoredItem> {
+            self.lists.get(list).unwrap_or_default()
+        }
+
+        fn join_completion(&self, stored: Vec<StoredItem>) -> Vec<TodoItem> {
+            stored
+                .into_iter()
+                .map(|item| {
+                    let completed = self.completed_items.contains(item.id);
+                    item.into_item(completed)
+                })
+                .collect()
+        }
+    }
+
+    impl Ownable for TodoList {
+        #[ink(message)]
+        fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        #[ink(message)]
+        fn pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Offers the admin role to `new_owner`. Only the current `owner` may do this,
+        /// and stays admin until `new_owner` accepts.
+        #[ink(message)]
+        fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), OwnableError> {
+            ownable::only_owner(self.env().caller(), self.owner)?;
+            self.pending_owner = Some(new_owner);
+            Ok(())
+        }
+
+        /// Makes the caller admin, if they are the `pending_owner`.
+        #[ink(message)]
+        fn accept_ownership(&mut self) -> Result<(), OwnableError> {
+            let caller = self.env().caller();
+            ownable::only_pending_owner(caller, self.pending_owner)?;
+            self.pending_owner = None;
+            self.owner = caller;
+            self.fee_recipient = caller;
+            Ok(())
+        }
+
+        /// Withdraws the offer made by `transfer_ownership`. Only the current `owner` may do this.
+        #[ink(message)]
+        fn cancel_ownership_transfer(&mut self) -> Result<(), OwnableError> {
+            ownable::only_owner(self.env().caller(), self.owner)?;
+            self.pending_owner = None;
+            Ok(())
+        }
+
+        /// Leaves the contract without an admin, so nobody can wipe the lists anymore.
+        /// Item fees keep going to the last owner, since the zero account can't receive them.
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> Result<(), OwnableError> {
+            ownable::only_owner(self.env().caller(), self.owner)?;
+            self.pending_owner = None;
+            self.owner = ownable::RENOUNCED;
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        IndexOutOfBounds,
+        EmptyDescription,
+        NotOwner,
+        TooManyTags,
+        ItemNotFound,
+        ListFull,
+        AlreadyMigrated,
+        FeePaymentFailed,
+        NotAuthorized,
+        TooManyNotes,
+        DescriptionTooLong,
+        NothingToUndo,
+        NotCompleted,
+        BountyUnclaimed,
+        TransferFailed,
+        DuplicateItem,
+        Overflow,
+        ZeroBounty,
+        TooManySubtasks,
+    }
+
+    impl From<OwnableError> for Error {
+        fn from(error: OwnableError) -> Self {
+            match error {
+                OwnableError::NotOwner | OwnableError::NotPendingOwner => Error::NotOwner,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn default_works() {
+            let todo_list = TodoList::default();
+            assert_eq!(todo_list.get_items().len(), 0);
+        }
+
+        #[ink::test]
+        fn add_item_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.get_items().len(), 1);
+            assert_eq!(todo_list.get_items()[0].description, "write tests");
+            assert!(!todo_list.get_items()[0].completed);
+        }
+
+        #[ink::test]
+        fn priority_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item_with_priority("fix bug".into(), Priority::High).is_ok());
+            assert!(todo_list.add_item_with_priority("tidy up".into(), Priority::Low).is_ok());
+            assert_eq!(todo_list.get_items()[0].priority, Priority::Medium);
+
+            assert_eq!(todo_list.set_priority(0, Priority::High), Ok(()));
+            assert_eq!(todo_list.set_priority(3, Priority::High), Err(Error::IndexOutOfBounds));
+            let high = todo_list.get_items_by_priority(Priority::High);
+            assert_eq!(high.len(), 2);
+            assert!(high.iter().all(|item| item.priority == Priority::High));
+            assert_eq!(todo_list.get_items_by_priority(Priority::Low)[0].description, "tidy up");
+        }
+
+        #[ink::test]
+        fn overdue_items_work() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item_with_due("pay rent".into(), 1000).is_ok());
+            assert!(todo_list.add_item_with_due("file taxes".into(), 5000).is_ok());
+            assert!(todo_list.add_item("someday".into()).is_ok());
+            assert_eq!(todo_list.get_items()[0].due_date, Some(1000));
+            assert_eq!(todo_list.get_overdue_items().len(), 0);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2000);
+            let overdue = todo_list.get_overdue_items();
+            assert_eq!(overdue.len(), 1);
+            assert_eq!(overdue[0].description, "pay rent");
+
+            // Completed items and moved deadlines are no longer overdue
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.set_due_date(1, Some(1500)), Ok(()));
+            assert_eq!(todo_list.set_due_date(2, Some(1500)), Ok(()));
+            assert_eq!(todo_list.set_due_date(2, None), Ok(()));
+            let overdue = todo_list.get_overdue_items();
+            assert_eq!(overdue.len(), 1);
+            assert_eq!(overdue[0].description, "file taxes");
+        }
+
+        #[ink::test]
+        fn items_by_status_work() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("done".into()).is_ok());
+            assert!(todo_list.add_item_with_due("late".into(), 500).is_ok());
+            assert!(todo_list.add_item_with_due("upcoming".into(), 5000).is_ok());
+            assert!(todo_list.add_item("shelved".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.archive_item(3), Ok(()));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+
+            let status = |status| -> Vec<String> {
+                todo_list.get_items_by_status(status).into_iter().map(|item| item.description).collect()
+            };
+            assert_eq!(status(ItemStatus::All), ["done", "late", "upcoming"]);
+            assert_eq!(status(ItemStatus::Completed), ["done"]);
+            assert_eq!(status(ItemStatus::Pending), ["late", "upcoming"]);
+            assert_eq!(status(ItemStatus::Overdue), ["late"]);
+            assert_eq!(status(ItemStatus::Archived), ["shelved"]);
+        }
+
+        #[ink::test]
+        fn sorted_items_work() {
+            let mut todo_list = TodoList::new();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(300);
+            assert!(todo_list.add_item_with_priority("undated".into(), Priority::High).is_ok());
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            assert!(todo_list.add_item_with_due("late".into(), 9000).is_ok());
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            assert!(todo_list.add_item_with_due("early".into(), 1000).is_ok());
+            assert_eq!(todo_list.set_priority(2, Priority::Low), Ok(()));
+
+            let order = |by| -> Vec<String> {
+                todo_list.get_items_sorted(by).into_iter().map(|item| item.description).collect()
+            };
+            assert_eq!(order(SortKey::DueDateAsc), ["early", "late", "undated"]);
+            assert_eq!(order(SortKey::DueDateDesc), ["late", "early", "undated"]);
+            assert_eq!(order(SortKey::PriorityDesc), ["undated", "late", "early"]);
+            assert_eq!(order(SortKey::CreatedAtAsc), ["late", "early", "undated"]);
+            assert_eq!(todo_list.get_items()[0].description, "undated");
+        }
+
+        #[ink::test]
+        fn due_within_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item_with_due("overdue".into(), 500).is_ok());
+            assert!(todo_list.add_item_with_due("due soon".into(), 1500).is_ok());
+            assert!(todo_list.add_item_with_due("due later".into(), 5000).is_ok());
+            assert!(todo_list.add_item_with_due("done".into(), 1200).is_ok());
+            assert!(todo_list.add_item("someday".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(3), Ok(()));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            let due = todo_list.get_due_within(1000);
+            assert_eq!(due.len(), 1);
+            assert_eq!(due[0].description, "due soon");
+            assert_eq!(todo_list.get_due_within(4000).len(), 2);
+            assert_eq!(todo_list.get_due_within(0).len(), 0);
+            assert_eq!(todo_list.get_due_within(u64::MAX).len(), 2);
+        }
+
+        #[ink::test]
+        fn recurring_items_work() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item_with_due("water plants".into(), 1000).is_ok());
+            assert!(todo_list.add_item("one-off".into()).is_ok());
+            assert_eq!(todo_list.set_recurrence(0, Some(500)), Ok(()));
+            assert_eq!(todo_list.add_tag(0, "home".into()), Ok(()));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1200);
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            let items = todo_list.get_items();
+            assert_eq!(items.len(), 3);
+            assert!(items[0].completed);
+            let next = &items[2];
+            assert_eq!(next.description, "water plants");
+            assert!(!next.completed);
+            assert_eq!(next.due_date, Some(1500));
+            assert_eq!(next.created_at, 1200);
+            assert_eq!(next.recurrence, Some(500));
+            assert_eq!(next.tags, ["home"]);
+
+            // Non-recurring items don't regenerate
+            assert_eq!(todo_list.mark_completed(1), Ok(()));
+            assert_eq!(todo_list.item_count(), 3);
+        }
+
+        #[ink::test]
+        fn subtasks_work() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("release".into()).is_ok());
+            assert_eq!(todo_list.add_subtask(0, "tag version".into()), Ok(()));
+            assert_eq!(todo_list.add_subtask(0, "publish".into()), Ok(()));
+            assert_eq!(todo_list.add_subtask(1, "publish".into()), Err(Error::IndexOutOfBounds));
+            assert_eq!(todo_list.get_items()[0].subtasks.len(), 2);
+
+            assert_eq!(todo_list.complete_subtask(0, 1), Ok(()));
+            assert_eq!(todo_list.complete_subtask(0, 2), Err(Error::IndexOutOfBounds));
+            let item = todo_list.get_item(0).unwrap();
+            assert!(item.subtasks[1].completed);
+            assert!(!item.completed);
+
+            // Completing the last subtask completes the parent
+            assert_eq!(todo_list.complete_subtask(0, 0), Ok(()));
+            assert!(todo_list.get_items()[0].completed);
+        }
+
+        #[ink::test]
+        fn add_subtask_validates_description() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("release".into()).is_ok());
+            assert_eq!(todo_list.add_subtask(0, String::new()), Err(Error::EmptyDescription));
+            let too_long = "a".repeat(todo_list.max_description_len() as usize + 1);
+            assert_eq!(todo_list.add_subtask(0, too_long), Err(Error::DescriptionTooLong));
+            assert!(todo_list.get_items()[0].subtasks.is_empty());
+        }
+
+        #[ink::test]
+        fn add_subtask_is_capped() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("release".into()).is_ok());
+            for i in 0..MAX_SUBTASKS {
+                assert_eq!(todo_list.add_subtask(0, format!("step{}", i)), Ok(()));
+            }
+            assert_eq!(todo_list.add_subtask(0, "one more".into()), Err(Error::TooManySubtasks));
+            assert_eq!(todo_list.get_items()[0].subtasks.len(), MAX_SUBTASKS);
+        }
+
+        #[ink::test]
+        fn archive_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item("deploy contract".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            // Completing doesn't archive
+            assert_eq!(todo_list.get_items().len(), 2);
+
+            assert_eq!(todo_list.archive_item(0), Ok(()));
+            assert_eq!(todo_list.archive_item(1), Err(Error::IndexOutOfBounds));
+            let items = todo_list.get_items();
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].description, "deploy contract");
+            let archived = todo_list.get_archived_items();
+            assert_eq!(archived.len(), 1);
+            assert_eq!(archived[0].description, "write tests");
+            assert!(archived[0].archived);
+            assert!(archived[0].completed);
+
+            assert_eq!(todo_list.unarchive_item(0), Ok(()));
+            assert_eq!(todo_list.get_archived_items().len(), 0);
+            assert_eq!(todo_list.get_items()[1].description, "write tests");
+            assert!(!todo_list.get_items()[1].archived);
+        }
+
+        #[ink::test]
+        fn timestamps_work() {
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.get_items()[0].completed_at, None);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(3000);
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            let item = todo_list.get_item(0).unwrap();
+            assert_eq!(item.created_at, 1000);
+            assert_eq!(item.completed_at, Some(3000));
+
+            // Re-completing keeps the original timestamp, reopening clears it
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(4000);
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.get_items()[0].completed_at, Some(3000));
+            assert_eq!(todo_list.toggle_completed(0), Ok(()));
+            assert_eq!(todo_list.get_items()[0].completed_at, None);
+        }
+
+        #[ink::test]
+        fn tags_work() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item("deploy contract".into()).is_ok());
+            assert!(todo_list.add_item("buy milk".into()).is_ok());
+            assert_eq!(todo_list.add_tag(0, "work".into()), Ok(()));
+            assert_eq!(todo_list.add_tag(1, "work".into()), Ok(()));
+            assert_eq!(todo_list.add_tag(1, "urgent".into()), Ok(()));
+            assert_eq!(todo_list.add_tag(3, "work".into()), Err(Error::IndexOutOfBounds));
+
+            let work = todo_list.get_items_by_tag("work".into());
+            assert_eq!(work.len(), 2);
+            assert_eq!(work[1].tags, ["work", "urgent"]);
+
+            assert_eq!(todo_list.remove_tag(0, "work".into()), Ok(()));
+            let work = todo_list.get_items_by_tag("work".into());
+            assert_eq!(work.len(), 1);
+            assert_eq!(work[0].description, "deploy contract");
+        }
+
+        #[ink::test]
+        fn too_many_tags() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            for i in 0..MAX_TAGS {
+                assert_eq!(todo_list.add_tag(0, format!("tag{}", i)), Ok(()));
+            }
+            assert_eq!(todo_list.add_tag(0, "one more".into()), Err(Error::TooManyTags));
+            assert_eq!(todo_list.get_items()[0].tags.len(), MAX_TAGS);
+        }
+
+        #[ink::test]
+        fn notes_work() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.add_note(0, "cover the cap".into()), Ok(()));
+            assert_eq!(todo_list.add_note(0, "and the getter".into()), Ok(()));
+            assert_eq!(todo_list.add_note(1, "nowhere".into()), Err(Error::IndexOutOfBounds));
+            assert_eq!(todo_list.get_notes(0), ["cover the cap", "and the getter"]);
+            assert_eq!(todo_list.get_notes(1), Vec::<String>::new());
+
+            for i in 2..MAX_NOTES {
+                assert_eq!(todo_list.add_note(0, format!("note{}", i)), Ok(()));
+            }
+            assert_eq!(todo_list.add_note(0, "one more".into()), Err(Error::TooManyNotes));
+            assert_eq!(todo_list.get_notes(0).len(), MAX_NOTES);
+        }
+
+        #[ink::test]
+        fn bounty_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("fix bug".into()).is_ok());
+            assert!(todo_list.add_item("unfunded".into()).is_ok());
+            assert_eq!(todo_list.assign(0, Some(accounts.bob)), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            ink::env::test::transfer_in::<ink::env::DefaultEnvironment>(300);
+            assert_eq!(todo_list.fund_item(accounts.alice, 0), Ok(()));
+            assert_eq!(todo_list.get_items_of(accounts.alice)[0].bounty, 300);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.delete_item(0), Err(Error::BountyUnclaimed));
+
+            // Only the assignee can claim, and only once the item is done
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(todo_list.claim_bounty(accounts.alice, 0), Err(Error::NotCompleted));
+            assert_eq!(todo_list.claim_bounty(accounts.alice, 1), Err(Error::NotAuthorized));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            todo_list.clear_completed();
+            assert_eq!(todo_list.get_items().len(), 2);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let before = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap();
+            assert_eq!(todo_list.claim_bounty(accounts.alice, 0), Ok(()));
+            let after = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap();
+            assert_eq!(after - before, 300);
+            assert_eq!(todo_list.get_items_of(accounts.alice)[0].bounty, 0);
+            assert_eq!(todo_list.claim_bounty(accounts.alice, 0), Ok(()));
+            assert_eq!(
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap(),
+                after
+            );
+        }
+
+        #[ink::test]
+        fn fund_item_rejects_overflow() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("fix bug".into()).is_ok());
+            let mut items = todo_list.get_items();
+            items[0].bounty = u128::MAX;
+            todo_list.save_items(items);
+
+            ink::env::test::transfer_in::<ink::env::DefaultEnvironment>(1);
+            assert_eq!(todo_list.fund_item(accounts.alice, 0), Err(Error::Overflow));
+            assert_eq!(todo_list.get_items()[0].bounty, u128::MAX);
+        }
+
+        #[ink::test]
+        fn bounties_leave_undo_alone() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("fix bug".into()).is_ok());
+            assert_eq!(todo_list.assign(0, Some(accounts.bob)), Ok(()));
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+
+            // Funding needs a value and doesn't count as a change to the list
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(todo_list.fund_item(accounts.alice, 0), Err(Error::ZeroBounty));
+            ink::env::test::transfer_in::<ink::env::DefaultEnvironment>(300);
+            assert_eq!(todo_list.fund_item(accounts.alice, 0), Ok(()));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.claim_bounty(accounts.alice, 0), Ok(()));
+            assert_eq!(todo_list.last_modified_of(accounts.alice), 0);
+
+            // The completion can still be undone, without bringing the claimed bounty back
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.undo(), Ok(()));
+            let item = &todo_list.get_items()[0];
+            assert!(!item.completed);
+            assert_eq!(item.bounty, 0);
+        }
+
+        #[ink::test]
+        fn assign_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item("deploy contract".into()).is_ok());
+            assert_eq!(todo_list.assign(0, Some(accounts.bob)), Ok(()));
+            assert_eq!(todo_list.assign(2, Some(accounts.bob)), Err(Error::IndexOutOfBounds));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(todo_list.add_item("review".into()).is_ok());
+            assert_eq!(todo_list.assign(0, Some(accounts.bob)), Ok(()));
+
+            let assigned = todo_list.get_items_assigned_to(accounts.bob);
+            assert_eq!(assigned.len(), 2);
+            assert_eq!(assigned[0].description, "write tests");
+            assert_eq!(assigned[1].description, "review");
+            // Assignment doesn't copy items into the assignee's list
+            assert_eq!(todo_list.get_items_of(accounts.bob).len(), 0);
+
+            // Reassign
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.assign(0, Some(accounts.django)), Ok(()));
+            assert_eq!(todo_list.get_items_assigned_to(accounts.bob).len(), 1);
+            assert_eq!(todo_list.get_items_assigned_to(accounts.django)[0].description, "write tests");
+        }
+
+        #[ink::test]
+        fn assignment_index_follows_items() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_items(vec!["write tests".into(), "deploy".into(), "water plants".into()]).is_ok());
+            assert_eq!(todo_list.set_recurrence(2, Some(1000)), Ok(()));
+            for index in 0..3 {
+                assert_eq!(todo_list.assign(index, Some(accounts.bob)), Ok(()));
+            }
+
+            // Unassigned and deleted items leave the index, undone deletes return to it
+            assert_eq!(todo_list.assign(1, None), Ok(()));
+            assert_eq!(todo_list.delete_item(0), Ok(()));
+            assert_eq!(todo_list.assigned_to.get(accounts.bob), Some(vec![(accounts.alice, 2)]));
+            assert_eq!(todo_list.undo(), Ok(()));
+            assert_eq!(todo_list.get_items_assigned_to(accounts.bob).len(), 2);
+
+            // Recurrences inherit the assignment, and clearing drops completed items
+            assert_eq!(todo_list.mark_completed(2), Ok(()));
+            assert_eq!(todo_list.get_items_assigned_to(accounts.bob).len(), 3);
+            todo_list.clear_completed();
+            let assigned = todo_list.get_items_assigned_to(accounts.bob);
+            assert_eq!(assigned.len(), 2);
+            assert!(assigned.iter().all(|item| !item.completed));
+            assert_eq!(todo_list.assigned_to.get(accounts.bob).map(|entries| entries.len()), Some(2));
+        }
+
+        #[ink::test]
+        fn search_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("Write tests".into()).is_ok());
+            assert!(todo_list.add_item("Deploy contract".into()).is_ok());
+            assert!(todo_list.add_item("Test deployment".into()).is_ok());
+
+            assert_eq!(todo_list.search("Write tests".into())[0].description, "Write tests");
+            let matches = todo_list.search("TEST".into());
+            assert_eq!(matches.len(), 2);
+            assert_eq!(matches[1].description, "Test deployment");
+            assert_eq!(todo_list.search("milk".into()).len(), 0);
+        }
+
+        #[ink::test]
+        fn find_item_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("Write tests".into()).is_ok());
+            assert!(todo_list.add_item("Deploy contract".into()).is_ok());
+            assert!(todo_list.add_item("Deploy contract".into()).is_ok());
+
+            let (index, item) = todo_list.find_item("Deploy contract".into()).unwrap();
+            assert_eq!(index, 1);
+            assert_eq!(item, todo_list.get_items()[1]);
+            assert_eq!(todo_list.find_item("deploy contract".into()), None);
+            assert_eq!(todo_list.find_item("Deploy".into()), None);
+        }
+
+        #[ink::test]
+        fn get_item_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.get_item(0).unwrap().description, "write tests");
+            assert_eq!(todo_list.get_item(1), None);
+        }
+
+        #[ink::test]
+        fn item_count_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item("deploy contract".into()).is_ok());
+            assert!(todo_list.add_item("write docs".into()).is_ok());
+            assert_eq!(todo_list.item_count(), 3);
+            assert_eq!(todo_list.mark_completed(1), Ok(()));
+            todo_list.clear_completed();
+            assert_eq!(todo_list.item_count(), 2);
+        }
+
+        #[ink::test]
+        fn counters_work() {
+            let mut todo_list = TodoList::new();
+            assert_eq!(todo_list.count_completed(), 0);
+            assert_eq!(todo_list.count_pending(), 0);
+
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item("deploy contract".into()).is_ok());
+            assert!(todo_list.add_item("write docs".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(1), Ok(()));
+            assert_eq!(todo_list.count_completed(), 1);
+            assert_eq!(todo_list.count_pending(), 2);
+
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.mark_completed(2), Ok(()));
+            assert_eq!(todo_list.count_completed(), 3);
+            assert_eq!(todo_list.count_pending(), 0);
+        }
+
+        #[ink::test]
+        fn get_items_paged_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert!(todo_list.add_item("third".into()).is_ok());
+
+            let page = todo_list.get_items_paged(2, 2);
+            assert_eq!(page.len(), 1);
+            assert_eq!(page[0].description, "third");
+            assert_eq!(todo_list.get_items_paged(0, 2).len(), 2);
+            assert_eq!(todo_list.get_items_paged(5, 2).len(), 0);
+            assert_eq!(todo_list.get_items_paged(0, 0).len(), 0);
+        }
+
+        #[ink::test]
+        fn status_getters_work() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert!(todo_list.add_item("third".into()).is_ok());
+            assert!(todo_list.add_item("fourth".into()).is_ok());
+            assert_eq!(todo_list.mark_many_completed(vec![0, 2]), Ok(()));
+
+            let descriptions = |items: Vec<TodoItem>| {
+                items.into_iter().map(|item| item.description).collect::<Vec<_>>()
+            };
+            assert_eq!(descriptions(todo_list.get_completed_items()), ["first", "third"]);
+            assert_eq!(descriptions(todo_list.get_pending_items()), ["second", "fourth"]);
+            assert_eq!(descriptions(todo_list.get_pending_items_paged(1, 5)), ["fourth"]);
+            assert_eq!(todo_list.get_pending_items_paged(2, 5).len(), 0);
+        }
+
+        #[ink::test]
+        fn mark_completed_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert!(todo_list.get_items()[0].completed);
+        }
+
+        #[ink::test]
+        fn mark_completed_not_found() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(1), Err(Error::ItemNotFound));
+            assert!(!todo_list.get_items()[0].completed);
+            assert_eq!(ink::env::test::recorded_events().count(), 2);
+        }
+
+        #[ink::test]
+        fn mark_many_completed_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert!(todo_list.add_item("third".into()).is_ok());
+            assert_eq!(todo_list.mark_many_completed(vec![0, 2]), Ok(()));
+            let items = todo_list.get_items();
+            assert!(items[0].completed);
+            assert!(!items[1].completed);
+            assert!(items[2].completed);
+        }
+
+        #[ink::test]
+        fn mark_many_completed_is_all_or_nothing() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert_eq!(todo_list.mark_many_completed(vec![0, 5, 1]), Err(Error::IndexOutOfBounds));
+            assert!(todo_list.get_items().iter().all(|item| !item.completed));
+        }
+
+        #[ink::test]
+        fn completion_percentage_works() {
+            let mut todo_list = TodoList::new();
+            assert_eq!(todo_list.completion_percentage(), 0);
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert!(todo_list.add_item("third".into()).is_ok());
+            assert_eq!(todo_list.completion_percentage(), 0);
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.completion_percentage(), 33);
+            assert_eq!(todo_list.mark_many_completed(vec![1, 2]), Ok(()));
+            assert_eq!(todo_list.completion_percentage(), 100);
+        }
+
+        #[ink::test]
+        fn toggle_completed_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.toggle_completed(0), Ok(()));
+            assert!(todo_list.get_items()[0].completed);
+            assert_eq!(todo_list.toggle_completed(0), Ok(()));
+            assert!(!todo_list.get_items()[0].completed);
+            assert_eq!(todo_list.toggle_completed(1), Err(Error::IndexOutOfBounds));
+        }
+
+        #[ink::test]
+        fn clear_completed_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item("deploy contract".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            todo_list.clear_completed();
+            assert_eq!(todo_list.get_items().len(), 1);
+            assert_eq!(todo_list.get_items()[0].description, "deploy contract");
+        }
+
+        #[ink::test]
+        fn completion_is_stored_apart_from_items() {
+            let descriptions = |items: Vec<TodoItem>| -> Vec<String> {
+                items.into_iter().map(|item| item.description).collect()
+            };
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_items(vec!["first".into(), "second".into(), "third".into()]).is_ok());
+            assert_eq!(todo_list.mark_completed(1), Ok(()));
+
+            // Completion follows the item id, not its position
+            assert_eq!(todo_list.move_item(1, 0), Ok(()));
+            assert_eq!(descriptions(todo_list.get_completed_items()), ["second"]);
+            assert_eq!(descriptions(todo_list.get_items_by_status(ItemStatus::Pending)), ["first", "third"]);
+
+            // and survives the archive and an undone delete
+            assert_eq!(todo_list.archive_item(0), Ok(()));
+            assert!(todo_list.get_archived_items()[0].completed);
+            assert_eq!(todo_list.get_completed_items().len(), 0);
+            assert_eq!(todo_list.unarchive_item(0), Ok(()));
+            assert_eq!(todo_list.delete_item(1), Ok(()));
+            assert!(!todo_list.completed_items.contains(1));
+            assert_eq!(todo_list.undo(), Ok(()));
+            assert_eq!(descriptions(todo_list.get_items_by_status(ItemStatus::Completed)), ["second"]);
+
+            // Removed items leave no completion entry behind
+            todo_list.clear_completed();
+            assert!(!todo_list.completed_items.contains(1));
+            assert_eq!(descriptions(todo_list.get_items()), ["first", "third"]);
+        }
+
+        /// We test that counting and fetching one item don't look up every item's completion.
+        #[ink::test]
+        fn item_queries_skip_the_completion_join() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_items(vec!["a".into(), "b".into(), "c".into(), "d".into()]).is_ok());
+            assert_eq!(todo_list.mark_completed(1), Ok(()));
+            let contract = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            let reads = || ink::env::test::get_contract_storage_rw::<ink::env::DefaultEnvironment>(&contract).0;
+
+            let before = reads();
+            assert_eq!(todo_list.item_count(), 4);
+            assert_eq!(reads() - before, 1);
+
+            let before = reads();
+            assert!(todo_list.get_item(1).unwrap().completed);
+            assert_eq!(reads() - before, 2);
+
+            let before = reads();
+            assert_eq!(todo_list.capacity_remaining(), todo_list.max_items - 4);
+            assert_eq!(reads() - before, 2);
+        }
+
+        #[ink::test]
+        fn add_item_emits_event() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item("deploy contract".into()).is_ok());
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 4);
+            let added = <ItemAdded as scale::Decode>::decode(&mut &events[2].data[..])
+                .expect("decoding failed");
+            assert_eq!(added.owner, accounts.alice);
+            assert_eq!(added.index, 1);
+            assert_eq!(added.description, "deploy contract");
+            let created = <TodoCreated as scale::Decode>::decode(&mut &events[3].data[..])
+                .expect("decoding failed");
+            assert_eq!(created.owner, accounts.alice);
+            assert_eq!(created.id, 1);
+        }
+
+        #[ink::test]
+        fn lifecycle_emits_events() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert_eq!(todo_list.edit_item(1, "edited".into()), Ok(()));
+            assert_eq!(todo_list.set_priority(1, Priority::High), Ok(()));
+            assert_eq!(todo_list.set_due_date(1, Some(100)), Ok(()));
+            assert_eq!(todo_list.delete_item(0), Ok(()));
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 8);
+            let created = <TodoCreated as scale::Decode>::decode(&mut &events[3].data[..])
+                .expect("decoding failed");
+            assert_eq!(created.id, 1);
+            for event in &events[4..7] {
+                let updated = <TodoUpdated as scale::Decode>::decode(&mut &event.data[..])
+                    .expect("decoding failed");
+                assert_eq!(updated.owner, accounts.alice);
+                assert_eq!(updated.id, 1);
+            }
+            let deleted = <TodoDeleted as scale::Decode>::decode(&mut &events[7].data[..])
+                .expect("decoding failed");
+            assert_eq!(deleted.owner, accounts.alice);
+            assert_eq!(deleted.id, 0);
+
+            // Failed mutations emit nothing
+            assert_eq!(todo_list.edit_item(0, "gone".into()), Err(Error::ItemNotFound));
+            assert_eq!(todo_list.delete_item(0), Err(Error::ItemNotFound));
+            assert_eq!(ink::env::test::recorded_events().count(), 8);
+        }
+
+        #[ink::test]
+        fn editor_can_add_complete_and_edit() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            todo_list.add_collaborator(accounts.bob, Role::Editor);
+            assert_eq!(todo_list.role_of(accounts.alice, accounts.bob), Some(Role::Editor));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.add_item_to(accounts.alice, "second".into()), Ok(()));
+            assert_eq!(todo_list.mark_completed_in(accounts.alice, 0), Ok(()));
+            assert_eq!(todo_list.edit_item_in(accounts.alice, 1, "edited".into()), Ok(()));
+            // Bob's own list is untouched
+            assert!(todo_list.get_items().is_empty());
+
+            let items = todo_list.get_items_of(accounts.alice);
+            assert_eq!(items.len(), 2);
+            assert!(items[0].completed);
+            assert_eq!(items[1].description, "edited");
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let added = <ItemAdded as scale::Decode>::decode(&mut &events[2].data[..])
+                .expect("decoding failed");
+            assert_eq!(added.owner, accounts.alice);
+        }
+
+        #[ink::test]
+        fn editor_cannot_delete() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            todo_list.add_collaborator(accounts.bob, Role::Editor);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.delete_item_in(accounts.alice, 0), Err(Error::NotAuthorized));
+            assert_eq!(todo_list.get_items_of(accounts.alice).len(), 1);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.delete_item_in(accounts.alice, 0), Ok(()));
+            assert!(todo_list.get_items().is_empty());
+        }
+
+        #[ink::test]
+        fn viewer_cannot_mutate() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            todo_list.add_collaborator(accounts.bob, Role::Viewer);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.add_item_to(accounts.alice, "second".into()), Err(Error::NotAuthorized));
+            assert_eq!(todo_list.mark_completed_in(accounts.alice, 0), Err(Error::NotAuthorized));
+            assert_eq!(todo_list.edit_item_in(accounts.alice, 0, "edited".into()), Err(Error::NotAuthorized));
+            assert_eq!(todo_list.delete_item_in(accounts.alice, 0), Err(Error::NotAuthorized));
+            let items = todo_list.get_items_of(accounts.alice);
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].description, "first");
+            assert!(!items[0].completed);
+
+            // Removed collaborators lose access entirely
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            todo_list.add_collaborator(accounts.bob, Role::Editor);
+            todo_list.remove_collaborator(accounts.bob);
+            assert_eq!(todo_list.role_of(accounts.alice, accounts.bob), None);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.add_item_to(accounts.alice, "second".into()), Err(Error::NotAuthorized));
+        }
+
+        #[ink::test]
+        fn completing_emits_event_once() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 3);
+            let completed = <ItemCompleted as scale::Decode>::decode(&mut &events[2].data[..])
+                .expect("decoding failed");
+            assert_eq!(completed.index, 0);
+
+            // Reopening emits nothing, completing again does
+            assert_eq!(todo_list.toggle_completed(0), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 3);
+            assert_eq!(todo_list.toggle_completed(0), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 4);
+        }
+
+        #[ink::test]
+        fn max_items_works() {
+            let mut todo_list = TodoList::with_max_items(3);
+            assert_eq!(todo_list.capacity_remaining(), 3);
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert_eq!(todo_list.add_items(vec!["second".into(), "third".into()]), Ok(()));
+            assert_eq!(todo_list.capacity_remaining(), 0);
+            assert_eq!(todo_list.add_item("fourth".into()), Err(Error::ListFull));
+            assert_eq!(todo_list.get_items().len(), 3);
+
+            // Archived items still take up space
+            assert_eq!(todo_list.archive_item(0), Ok(()));
+            assert_eq!(todo_list.add_item("fourth".into()), Err(Error::ListFull));
+            assert_eq!(todo_list.delete_item(1), Ok(()));
+            assert_eq!(todo_list.add_items(vec!["fourth".into(), "fifth".into()]), Err(Error::ListFull));
+            assert!(todo_list.add_item("fourth".into()).is_ok());
+        }
+
+        #[ink::test]
+        fn add_items_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert_eq!(todo_list.add_items(vec!["second".into(), "third".into()]), Ok(()));
+            let items = todo_list.get_items();
+            assert_eq!(items.len(), 3);
+            assert_eq!(items[2].description, "third");
+            assert!(!items[2].completed);
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 6);
+            let added = <ItemAdded as scale::Decode>::decode(&mut &events[4].data[..])
+                .expect("decoding failed");
+            assert_eq!(added.index, 2);
+            assert_eq!(added.description, "third");
+        }
+
+        #[ink::test]
+        fn add_items_is_all_or_nothing() {
+            let mut todo_list = TodoList::new();
+            assert_eq!(
+                todo_list.add_items(vec!["first".into(), String::new()]),
+                Err(Error::EmptyDescription)
+            );
+            assert_eq!(todo_list.get_items().len(), 0);
+            assert_eq!(ink::env::test::recorded_events().count(), 0);
+        }
+
+        #[ink::test]
+        fn lists_are_per_user() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("alice's task".into()).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.get_items().len(), 0);
+            assert!(todo_list.add_item("bob's task".into()).is_ok());
+            assert!(todo_list.add_item("another task".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(1), Ok(()));
+
+            assert_eq!(todo_list.get_items().len(), 2);
+            let alice_items = todo_list.get_items_of(accounts.alice);
+            assert_eq!(alice_items.len(), 1);
+            assert_eq!(alice_items[0].description, "alice's task");
+            assert!(!alice_items[0].completed);
+        }
+
+        #[ink::test]
+        fn wipe_all_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("alice's task".into()).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(todo_list.add_item("bob's task".into()).is_ok());
+            assert_eq!(todo_list.wipe_all(), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.wipe_all(), Ok(()));
+            assert_eq!(todo_list.get_items_of(accounts.alice).len(), 0);
+            assert_eq!(todo_list.get_items_of(accounts.bob).len(), 0);
+            assert_eq!(todo_list.user_count(), 0);
+
+            // Users register again once they add a new item
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(todo_list.add_item("bob's next task".into()).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.user_count(), 1);
+            assert_eq!(todo_list.export_all().unwrap()[0].0, accounts.bob);
+        }
+
+        #[ink::test]
+        fn wipe_all_keeps_bountied_items() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut todo_list = TodoList::new();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(todo_list.add_items(vec!["funded".into(), "archived".into(), "plain".into()]).is_ok());
+            assert_eq!(todo_list.assign(0, Some(accounts.charlie)), Ok(()));
+            ink::env::test::transfer_in::<ink::env::DefaultEnvironment>(300);
+            assert_eq!(todo_list.fund_item(accounts.bob, 0), Ok(()));
+            assert_eq!(todo_list.fund_item(accounts.bob, 1), Ok(()));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(todo_list.archive_item(1), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.wipe_all(), Ok(()));
+            let items = todo_list.get_items_of(accounts.bob);
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].description, "funded");
+            assert_eq!(todo_list.export_all().unwrap().len(), 1);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.get_archived_items()[0].description, "archived");
+            assert_eq!(todo_list.mark_completed(items[0].id), Ok(()));
+
+            // The bounty is still claimable after the wipe
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(todo_list.claim_bounty(accounts.bob, 0), Ok(()));
+        }
+
+        #[ink::test]
+        fn undo_delete_restores_position() {
+            let mut todo_list = TodoList::new();
+            assert_eq!(todo_list.undo(), Err(Error::NothingToUndo));
+            assert!(todo_list.add_items(vec!["first".into(), "second".into(), "third".into()]).is_ok());
+
+            assert_eq!(todo_list.delete_item(1), Ok(()));
+            assert_eq!(todo_list.get_items().len(), 2);
+            assert_eq!(todo_list.undo(), Ok(()));
+            let items = todo_list.get_items();
+            assert_eq!(items.len(), 3);
+            assert_eq!(items[1].description, "second");
+            assert_eq!(items[1].id, 1);
+
+            // Undo works once
+            assert_eq!(todo_list.undo(), Err(Error::NothingToUndo));
+
+            // Any later change discards the undo record
+            assert_eq!(todo_list.delete_item(0), Ok(()));
+            assert!(todo_list.add_item("fourth".into()).is_ok());
+            assert_eq!(todo_list.undo(), Err(Error::NothingToUndo));
+            assert_eq!(todo_list.get_items().len(), 3);
+        }
+
+        #[ink::test]
+        fn undo_complete_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("water plants".into()).is_ok());
+            assert_eq!(todo_list.set_recurrence(0, Some(1000)), Ok(()));
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.get_items().len(), 2);
+
+            // The next occurrence goes away again
+            assert_eq!(todo_list.undo(), Ok(()));
+            let items = todo_list.get_items();
+            assert_eq!(items.len(), 1);
+            assert!(!items[0].completed);
+            assert_eq!(items[0].completed_at, None);
+            assert_eq!(todo_list.undo(), Err(Error::NothingToUndo));
+        }
+
+        #[ink::test]
+        fn last_modified_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert_eq!(todo_list.last_modified(), 0);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.last_modified(), 100);
+
+            // Reads leave it alone
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            assert_eq!(todo_list.get_items().len(), 1);
+            assert_eq!(todo_list.get_pending_items().len(), 1);
+            assert_eq!(todo_list.last_modified(), 100);
+
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.last_modified(), 200);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(300);
+            assert_eq!(todo_list.delete_item(0), Ok(()));
+            assert_eq!(todo_list.last_modified(), 300);
+
+            // Lists are tracked separately
+            assert_eq!(todo_list.last_modified_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn export_all_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("alice's task".into()).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(todo_list.add_item("bob's task".into()).is_ok());
+            assert!(todo_list.add_item("another task".into()).is_ok());
+            assert_eq!(todo_list.export_all(), Err(Error::NotOwner));
+            assert_eq!(todo_list.export_users(0, 1), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let export = todo_list.export_all().unwrap();
+            assert_eq!(export.len(), 2);
+            assert_eq!(export[0].0, accounts.alice);
+            assert_eq!(export[0].1, todo_list.get_items_of(accounts.alice));
+            assert_eq!(export[1].0, accounts.bob);
+            assert_eq!(export[1].1.len(), 2);
+            assert_eq!(export[1].1[1].description, "another task");
+
+            let page = todo_list.export_users(1, 5).unwrap();
+            assert_eq!(page, export[1..].to_vec());
+            assert_eq!(todo_list.export_users(0, 1).unwrap(), export[..1].to_vec());
+            assert_eq!(todo_list.export_users(2, 1).unwrap(), Vec::new());
+            assert_eq!(todo_list.export_users(u32::MAX, u32::MAX).unwrap(), Vec::new());
+            assert_eq!(todo_list.user_count(), 2);
+        }
+
+        #[ink::test]
+        fn mutations_are_owner_only() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("alice's task".into()).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.mark_completed(0), Err(Error::ItemNotFound));
+            assert_eq!(todo_list.delete_item(0), Err(Error::ItemNotFound));
+            assert_eq!(todo_list.transfer_ownership(accounts.bob), Err(OwnableError::NotOwner));
+            assert_eq!(todo_list.get_items_of(accounts.alice).len(), 1);
+            assert!(!todo_list.get_items_of(accounts.alice)[0].completed);
+        }
+
+        #[ink::test]
+        fn transfer_ownership_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert_eq!(todo_list.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(todo_list.owner(), accounts.alice);
+            assert_eq!(todo_list.pending_owner(), Some(accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.accept_ownership(), Ok(()));
+            assert_eq!(todo_list.owner(), accounts.bob);
+            assert_eq!(todo_list.pending_owner(), None);
+            assert_eq!(todo_list.wipe_all(), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.wipe_all(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn accept_ownership_requires_pending_owner() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert_eq!(todo_list.transfer_ownership(accounts.bob), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(todo_list.accept_ownership(), Err(OwnableError::NotPendingOwner));
+            assert_eq!(todo_list.owner(), accounts.alice);
+            assert_eq!(todo_list.pending_owner(), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn cancel_ownership_transfer_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert_eq!(todo_list.transfer_ownership(accounts.bob), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.cancel_ownership_transfer(), Err(OwnableError::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.cancel_ownership_transfer(), Ok(()));
+            assert_eq!(todo_list.pending_owner(), None);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.accept_ownership(), Err(OwnableError::NotPendingOwner));
+            assert_eq!(todo_list.owner(), accounts.alice);
+        }
+
+        #[ink::test]
+        fn with_item_fee_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let todo_list = TodoList::with_item_fee(accounts.django, 5);
+            assert_eq!(todo_list.fee_token(), Some(accounts.django));
+            assert_eq!(todo_list.item_fee(), 5);
+            assert_eq!(TodoList::new().fee_token(), None);
+        }
+
+        #[ink::test]
+        fn migrate_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            assert_eq!(todo_list.version(), STORAGE_VERSION);
+            assert_eq!(todo_list.migrate(), Err(Error::AlreadyMigrated));
+
+            todo_list.storage_version = STORAGE_VERSION - 1;
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.migrate(), Err(Error::NotOwner));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.migrate(), Ok(()));
+            assert_eq!(todo_list.version(), STORAGE_VERSION);
+            assert_eq!(todo_list.migrate(), Err(Error::AlreadyMigrated));
+        }
+
+        #[ink::test]
+        fn renounce_ownership_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::new();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.renounce_ownership(), Err(OwnableError::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(todo_list.renounce_ownership(), Ok(()));
+            assert_eq!(todo_list.owner(), ownable::RENOUNCED);
+            assert_eq!(todo_list.wipe_all(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn fee_recipient_follows_accepted_owner_but_not_renounce() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut todo_list = TodoList::with_item_fee(accounts.django, 5);
+            assert_eq!(todo_list.fee_recipient(), accounts.alice);
+
+            assert_eq!(todo_list.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(todo_list.fee_recipient(), accounts.alice);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(todo_list.accept_ownership(), Ok(()));
+            assert_eq!(todo_list.fee_recipient(), accounts.bob);
+
+            assert_eq!(todo_list.renounce_ownership(), Ok(()));
+            assert_eq!(todo_list.fee_recipient(), accounts.bob);
+        }
+
+        #[ink::test]
+        fn clear_all_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert!(todo_list.add_item("deploy contract".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            todo_list.clear_all();
+            assert_eq!(todo_list.get_items().len(), 0);
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let cleared = <ListCleared as scale::Decode>::decode(&mut &events[5].data[..])
+                .expect("decoding failed");
+            assert_eq!(cleared.count, 2);
+        }
+
+        #[ink::test]
+        fn delete_item_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert!(todo_list.add_item("third".into()).is_ok());
+            assert!(todo_list.add_item("fourth".into()).is_ok());
+            assert!(todo_list.add_item("fifth".into()).is_ok());
+
+            assert_eq!(todo_list.delete_item(0), Ok(()));
+            assert_eq!(todo_list.delete_item(2), Ok(()));
+            assert_eq!(todo_list.delete_item(4), Ok(()));
+            let items = todo_list.get_items();
+            assert_eq!(items.len(), 2);
+            assert_eq!(items[0].description, "second");
+            assert_eq!(items[1].description, "fourth");
+        }
+
+        #[ink::test]
+        fn delete_item_not_found() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.delete_item(1), Err(Error::ItemNotFound));
+            assert_eq!(todo_list.get_items().len(), 1);
+        }
+
+        #[ink::test]
+        fn ids_survive_deletes() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert!(todo_list.add_item("third".into()).is_ok());
+            assert_eq!(todo_list.delete_item(0), Ok(()));
+            assert_eq!(todo_list.delete_item(1), Ok(()));
+            assert!(todo_list.add_item("fourth".into()).is_ok());
+
+            let items = todo_list.get_items();
+            assert_eq!(items[0].id, 2);
+            assert_eq!(items[0].description, "third");
+            // Ids aren't reused
+            assert_eq!(items[1].id, 3);
+            assert_eq!(todo_list.mark_completed(2), Ok(()));
+            assert!(todo_list.get_items()[0].completed);
+        }
+
+        #[ink::test]
+        fn move_item_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("first".into()).is_ok());
+            assert!(todo_list.add_item("second".into()).is_ok());
+            assert!(todo_list.add_item_with_priority("third".into(), Priority::High).is_ok());
+            assert_eq!(todo_list.mark_completed(2), Ok(()));
+
+            // Up
+            assert_eq!(todo_list.move_item(2, 0), Ok(()));
+            let descriptions = |todo_list: &TodoList| {
+                todo_list.get_items().into_iter().map(|item| item.description).collect::<Vec<_>>()
+            };
+            assert_eq!(descriptions(&todo_list), ["third", "first", "second"]);
+            assert!(todo_list.get_items()[0].completed);
+            assert_eq!(todo_list.get_items()[0].priority, Priority::High);
+
+            // Down
+            assert_eq!(todo_list.move_item(0, 1), Ok(()));
+            assert_eq!(descriptions(&todo_list), ["first", "third", "second"]);
+
+            // To the end
+            assert_eq!(todo_list.move_item(0, 10), Ok(()));
+            assert_eq!(descriptions(&todo_list), ["third", "second", "first"]);
+            assert_eq!(todo_list.move_item(3, 0), Err(Error::IndexOutOfBounds));
+        }
+
+        #[ink::test]
+        fn duplicates_can_be_rejected() {
+            let mut todo_list = TodoList::with_reject_duplicates(true);
+            assert!(todo_list.add_item("water plants".into()).is_ok());
+            assert_eq!(todo_list.add_item("water plants".into()), Err(Error::DuplicateItem));
+            assert_eq!(
+                todo_list.add_items(vec!["feed cat".into(), "water plants".into()]),
+                Err(Error::DuplicateItem)
+            );
+            assert_eq!(
+                todo_list.add_items(vec!["feed cat".into(), "feed cat".into()]),
+                Err(Error::DuplicateItem)
+            );
+            assert_eq!(todo_list.get_items().len(), 1);
+
+            // Completed items can be added again
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert!(todo_list.add_item("water plants".into()).is_ok());
+            assert_eq!(todo_list.get_items().len(), 2);
+
+            // Without the flag duplicates are fine
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("water plants".into()).is_ok());
+            assert!(todo_list.add_item("water plants".into()).is_ok());
+        }
+
+        #[ink::test]
+        fn description_length_is_bounded() {
+            let mut todo_list = TodoList::new();
+            let max = todo_list.max_description_len() as usize;
+            assert_eq!(max, 256);
+            assert_eq!(todo_list.add_item("a".repeat(max)), Ok(()));
+            assert_eq!(todo_list.add_item("a".repeat(max + 1)), Err(Error::DescriptionTooLong));
+
+            // Bytes are counted, so `max / 2` two-byte characters just fit
+            let accented = "é".repeat(max / 2);
+            assert_eq!(accented.len(), max);
+            assert_eq!(todo_list.add_item(accented.clone()), Ok(()));
+            assert_eq!(todo_list.add_item(accented.clone() + "a"), Err(Error::DescriptionTooLong));
+
+            assert_eq!(
+                todo_list.add_items(vec!["short".into(), "a".repeat(max + 1)]),
+                Err(Error::DescriptionTooLong)
+            );
+            assert_eq!(todo_list.get_items().len(), 2);
+
+            assert_eq!(todo_list.edit_item(0, accented.clone() + "a"), Err(Error::DescriptionTooLong));
+            assert_eq!(todo_list.edit_item(0, accented.clone()), Ok(()));
+            assert_eq!(todo_list.get_items()[0].description, accented);
+        }
+
+        #[ink::test]
+        fn edit_item_works() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("wrte tests".into()).is_ok());
+            assert_eq!(todo_list.mark_completed(0), Ok(()));
+            assert_eq!(todo_list.edit_item(0, "write tests".into()), Ok(()));
+            assert_eq!(todo_list.get_items()[0].description, "write tests");
+            assert!(todo_list.get_items()[0].completed);
+        }
+
+        #[ink::test]
+        fn edit_item_rejects_invalid_input() {
+            let mut todo_list = TodoList::new();
+            assert!(todo_list.add_item("write tests".into()).is_ok());
+            assert_eq!(todo_list.edit_item(0, String::new()), Err(Error::EmptyDescription));
+            assert_eq!(todo_list.edit_item(1, "deploy".into()), Err(Error::ItemNotFound));
+            assert_eq!(todo_list.get_items()[0].description, "write tests");
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::{ChainBackend, ContractsBackend};
+        use token::token::{Token, TokenRef};
+        use token::PSP22;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn add_item_charges_fee(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given - Bob holds tokens and approved the todo list
+            let mut token_constructor = TokenRef::new();
+            let token = client
+                .instantiate("token", &ink_e2e::alice(), &mut token_constructor)
+                .submit()
+                .await
+                .expect("token instantiate failed");
+            let mut token_call_builder = token.call_builder::<Token>();
+
+            let mut constructor = TodoListRef::with_item_fee(token.account_id, 10);
+            let todo_list = client
+                .instantiate("todo_list", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("todo_list instantiate failed");
+            let mut call_builder = todo_list.call_builder::<TodoList>();
+
+            let mint = token_call_builder.mint(ink_e2e::bob().account_id(), 100);
+            let _mint_result = client
+                .call(&ink_e2e::alice(), &mint)
+                .submit()
+                .await
+                .expect("mint failed");
+            let approve = token_call_builder.approve(todo_list.account_id, 10);
+            let _approve_result = client
+                .call(&ink_e2e::bob(), &approve)
+                .submit()
+                .await
+                .expect("approve failed");
+
+            // When - Bob adds an item
+            let add_item = call_builder.add_item("write tests".into());
+            let _add_result = client
+                .call(&ink_e2e::bob(), &add_item)
+                .submit()
+                .await
+                .expect("add_item failed");
+
+            // Then - the fee moved from Bob to the owner
+            let bob_balance = token_call_builder.balance_of(ink_e2e::bob().account_id());
+            let bob_balance_result = client.call(&ink_e2e::alice(), &bob_balance).dry_run().await?;
+            assert_eq!(bob_balance_result.return_value(), 90);
+            let alice_balance = token_call_builder.balance_of(ink_e2e::alice().account_id());
+            let alice_balance_result = client.call(&ink_e2e::alice(), &alice_balance).dry_run().await?;
+            assert_eq!(alice_balance_result.return_value(), 10);
+
+            // And - without allowance left the add is reverted
+            let add_item = call_builder.add_item("deploy".into());
+            let add_result = client.call(&ink_e2e::bob(), &add_item).dry_run().await?;
+            assert_eq!(add_result.return_value(), Err(Error::FeePaymentFailed));
+
+            // When - the owner renounces and Bob approves another fee
+            let renounce = call_builder.renounce_ownership();
+            let _renounce_result = client
+                .call(&ink_e2e::alice(), &renounce)
+                .submit()
+                .await
+                .expect("renounce failed");
+            let _approve_result = client
+                .call(&ink_e2e::bob(), &approve)
+                .submit()
+                .await
+                .expect("approve failed");
+            let _add_result = client
+                .call(&ink_e2e::bob(), &add_item)
+                .submit()
+                .await
+                .expect("add_item after renounce failed");
+
+            // Then - the fee still reaches the former owner
+            let alice_balance_result = client.call(&ink_e2e::alice(), &alice_balance).dry_run().await?;
+            assert_eq!(alice_balance_result.return_value(), 20);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn bounty_is_funded_and_claimed(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given - Alice's item is assigned to Bob
+            let mut constructor = TodoListRef::new();
+            let todo_list = client
+                .instantiate("todo_list", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("todo_list instantiate failed");
+            let mut call_builder = todo_list.call_builder::<TodoList>();
+            let alice = ink_e2e::alice().account_id();
+
+            let add_item = call_builder.add_item("fix bug".into());
+            let _add_result = client
+                .call(&ink_e2e::alice(), &add_item)
+                .submit()
+                .await
+                .expect("add_item failed");
+            let assign = call_builder.assign(0, Some(ink_e2e::bob().account_id()));
+            let _assign_result = client
+                .call(&ink_e2e::alice(), &assign)
+                .submit()
+                .await
+                .expect("assign failed");
+            let native_before = client.free_balance(todo_list.account_id).await?;
+
+            // When - Charlie funds the item
+            let fund_item = call_builder.fund_item(alice, 0);
+            let _fund_result = client
+                .call(&ink_e2e::charlie(), &fund_item)
+                .value(1_000_000)
+                .submit()
+                .await
+                .expect("fund_item failed");
+
+            // Then - the contract holds the bounty, which can't be claimed yet
+            assert_eq!(client.free_balance(todo_list.account_id).await?, native_before + 1_000_000);
+            let claim_bounty = call_builder.claim_bounty(alice, 0);
+            let claim_result = client.call(&ink_e2e::bob(), &claim_bounty).dry_run().await?;
+            assert_eq!(claim_result.return_value(), Err(Error::NotCompleted));
+
+            // When - Alice completes the item and Bob claims
+            let mark_completed = call_builder.mark_completed(0);
+            let _complete_result = client
+                .call(&ink_e2e::alice(), &mark_completed)
+                .submit()
+                .await
+                .expect("mark_completed failed");
+            let _claim_result = client
+                .call(&ink_e2e::bob(), &claim_bounty)
+                .submit()
+                .await
+                .expect("claim_bounty failed");
+
+            // Then - the bounty left the contract
+            assert_eq!(client.free_balance(todo_list.account_id).await?, native_before);
+            let get_items_of = call_builder.get_items_of(alice);
+            let items_result = client.call(&ink_e2e::alice(), &get_items_of).dry_run().await?;
+            assert_eq!(items_result.return_value()[0].bounty, 0);
+
+            Ok(())
         }
     }
 }
@@ -1,15 +1,283 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+
+/// Errors defined by the PSP22 standard.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PSP22Error {
+    /// Any error that doesn't fit the other variants, described by a string.
+    Custom(String),
+    /// Returned if there is not enough balance to perform the operation.
+    InsufficientBalance,
+    /// Returned if there is not enough allowance to perform the operation.
+    InsufficientAllowance,
+    /// Returned if the recipient is the zero address.
+    ZeroRecipientAddress,
+    /// Returned if the sender is the zero address.
+    ZeroSenderAddress,
+    /// Returned if a safe transfer check failed.
+    SafeTransferCheckFailed(String),
+}
+
+/// The PSP22 fungible token standard.
+/// Message selectors are derived from the `PSP22::` prefixed names, as the spec requires.
+#[ink::trait_definition]
+pub trait PSP22 {
+    /// Returns the total token supply.
+    #[ink(message)]
+    fn total_supply(&self) -> u128;
+
+    /// Returns the account balance of `owner`, 0 if it has none.
+    #[ink(message)]
+    fn balance_of(&self, owner: AccountId) -> u128;
+
+    /// Returns the amount `spender` may still withdraw from `owner`.
+    #[ink(message)]
+    fn allowance(&self, owner: AccountId, spender: AccountId) -> u128;
+
+    /// Transfers `value` tokens from the caller to `to`.
+    #[ink(message)]
+    fn transfer(&mut self, to: AccountId, value: u128, data: Vec<u8>) -> Result<(), PSP22Error>;
+
+    /// Transfers `value` tokens from `from` to `to` using the caller's allowance.
+    #[ink(message)]
+    fn transfer_from(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        value: u128,
+        data: Vec<u8>,
+    ) -> Result<(), PSP22Error>;
+
+    /// Allows `spender` to withdraw up to `value` tokens from the caller.
+    #[ink(message)]
+    fn approve(&mut self, spender: AccountId, value: u128) -> Result<(), PSP22Error>;
+
+    /// Increases the allowance of `spender` by `delta_value`.
+    #[ink(message)]
+    fn increase_allowance(&mut self, spender: AccountId, delta_value: u128) -> Result<(), PSP22Error>;
+
+    /// Decreases the allowance of `spender` by `delta_value`.
+    #[ink(message)]
+    fn decrease_allowance(&mut self, spender: AccountId, delta_value: u128) -> Result<(), PSP22Error>;
+}
+
+/// Hook a contract implements to accept tokens sent with `safe_transfer`.
+#[ink::trait_definition]
+pub trait TokenReceiver {
+    /// Called after `amount` tokens from `from` were credited to the receiving contract.
+    /// Returning an error rejects the transfer.
+    #[ink(message)]
+    fn on_token_received(&mut self, from: AccountId, amount: u128, data: Vec<u8>) -> Result<(), String>;
+}
+
+/// Hook a contract implements to be notified of allowances granted with `approve_and_call`.
+#[ink::trait_definition]
+pub trait ApprovalReceiver {
+    /// Called after `owner` allowed the receiving contract to spend `amount` of their tokens.
+    /// The allowance may be spent from within the hook. Returning an error rejects the approval.
+    #[ink(message)]
+    fn on_approval_received(&mut self, owner: AccountId, amount: u128, data: Vec<u8>) -> Result<(), String>;
+}
+
 #[ink::contract]
-mod token {
+pub mod token {
+    use crate::{PSP22, PSP22Error};
+    use events::{
+        Approval, DelegateChanged, EmergencyWithdrawal, OwnershipTransferred, Paused, SupplyChanged, Transfer,
+        TransferWithMemo, Unpaused,
+    };
+    use ownable::{Ownable, OwnableError};
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::CallFlags;
+    use ink::env::hash::Blake2x256;
+    use ink::prelude::format;
+    use ink::prelude::string::String;
+    use ink::prelude::vec::Vec;
     use ink::storage::Mapping;
 
+    /// A linear vesting schedule unlocking `total` tokens over `duration` milliseconds.
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct VestingSchedule {
+        pub total: u128,
+        pub start: Timestamp,
+        pub duration: u64,
+        pub claimed: u128,
+    }
+
+    /// Groups of messages that can be paused on their own with `set_operation_paused`.
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Operation {
+        /// Every message that moves existing tokens between accounts.
+        Transfer,
+        /// Every message that creates new tokens, including claims and the faucet.
+        Mint,
+    }
+
+    /// Tokens `from` holds for an account until `unlock_at`.
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Lock {
+        pub from: AccountId,
+        pub amount: u128,
+        pub unlock_at: Timestamp,
+    }
+
+    /// An offer by `maker` to swap `give` escrowed tokens for `want_amount` of the
+    /// PSP22 `want_token`, open to `counterparty` until `expires_at`.
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Swap {
+        pub maker: AccountId,
+        pub counterparty: AccountId,
+        pub give: u128,
+        pub want_token: AccountId,
+        pub want_amount: u128,
+        pub expires_at: Timestamp,
+    }
+
+    /// Voting power of a delegate as of a block, stored as (block, votes).
+    pub type Checkpoint = (BlockNumber, u128);
+
+    /// Storage layout version this code expects, bumped whenever `migrate` has work to do.
+    pub const STORAGE_VERSION: u16 = 1;
+
+    /// How long a swap can be accepted after it was created, in milliseconds.
+    pub const SWAP_DURATION: u64 = 7 * 24 * 60 * 60 * 1000;
+
+    /// Maximum number of accounts `balances_of` looks up in one call.
+    pub const MAX_BALANCE_QUERIES: usize = 100;
+
+    /// Maximum number of pending locks an account can hold, which bounds the cost of
+    /// `release_locks` and `locked_balance_of`.
+    pub const MAX_LOCKS: usize = 32;
+
+    /// Longest time a lock can be held for, in milliseconds.
+    pub const MAX_LOCK_DURATION: u64 = 365 * 24 * 60 * 60 * 1000;
+
+    /// Allowance that never runs out: spending from it leaves it untouched.
+    /// Any smaller allowance goes down by every amount spent.
+    pub const UNLIMITED_ALLOWANCE: u128 = u128::MAX;
+
     /// Defines the storage of your contract.
     /// Stores a mapping from AccountId to u128 for token balances.
     #[ink(storage)]
     pub struct Token {
         /// Mapping from AccountId to token balance (u128)
         balances: Mapping<AccountId, u128>,
+        /// Total amount of tokens minted across all accounts
+        total_supply: u128,
+        /// Mapping from (owner, spender) to the amount the spender may use
+        allowances: Mapping<(AccountId, AccountId), u128>,
+        /// Human readable name of the token
+        name: String,
+        /// Ticker symbol of the token
+        symbol: String,
+        /// Number of decimals used to display balances
+        decimals: u8,
+        /// Admin account, also allowed to mint, the zero account once renounced
+        owner: AccountId,
+        /// Set of accounts allowed to mint besides the owner
+        minters: Mapping<AccountId, ()>,
+        /// Maximum total supply that minting may reach
+        cap: u128,
+        /// Whether token movement is currently frozen
+        paused: bool,
+        /// Number of accounts holding a non-zero balance
+        holder_count: u32,
+        /// Set of accounts blocked from sending or receiving tokens
+        frozen: Mapping<AccountId, ()>,
+        /// Id of the latest snapshot, 0 until the first one is taken
+        snapshot_id: u32,
+        /// Balances recorded lazily per (snapshot id, account)
+        snapshot_balances: Mapping<(u32, AccountId), u128>,
+        /// Reentrancy lock held while a guarded message runs
+        locked: bool,
+        /// Fee charged on `transfer` in basis points (1/10000)
+        fee_bps: u16,
+        /// Account credited with transfer fees
+        fee_collector: AccountId,
+        /// Vesting schedule per beneficiary
+        vesting: Mapping<AccountId, VestingSchedule>,
+        /// Root of the merkle tree of (account, amount) airdrop leaves
+        merkle_root: [u8; 32],
+        /// Set of accounts that already claimed their airdrop
+        claimed: Mapping<AccountId, ()>,
+        /// Whether the all-zero account may receive tokens, e.g. as a burn sink
+        allow_zero_address: bool,
+        /// Cumulative amount of tokens ever minted
+        total_minted: u128,
+        /// Cumulative amount of tokens ever burned
+        total_burned: u128,
+        /// Largest balance a single non-exempt account may hold
+        max_balance: u128,
+        /// Set of accounts exempt from the maximum balance
+        max_balance_exempt: Mapping<AccountId, ()>,
+        /// Time-locked tokens per account, not yet part of its balance
+        locks: Mapping<AccountId, Vec<Lock>>,
+        /// Account each delegator has delegated its voting power to
+        delegates: Mapping<AccountId, AccountId>,
+        /// Voting power history per delegate as (block, votes), indexed from 0
+        checkpoints: Mapping<(AccountId, u32), Checkpoint>,
+        /// Number of checkpoints written per delegate
+        checkpoint_count: Mapping<AccountId, u32>,
+        /// Layout version of the data in storage
+        storage_version: u16,
+        /// Reflection charged on transfers in basis points, shared among all holders
+        reflection_bps: u16,
+        /// Reflections earned per token held since deployment, scaled by 2^64
+        magnified_dividend_per_share: u128,
+        /// Value of `magnified_dividend_per_share` when each account was last settled
+        dividend_checkpoints: Mapping<AccountId, u128>,
+        /// Reflections settled to each account but not yet withdrawn
+        dividends_owed: Mapping<AccountId, u128>,
+        /// Tokens minted per faucet claim, zero when the faucet is disabled
+        drip_amount: u128,
+        /// Minimum time in milliseconds between two faucet claims of an account
+        cooldown: u64,
+        /// Time of each account's latest faucet claim
+        last_claim: Mapping<AccountId, Timestamp>,
+        /// Sr25519 public key signing mint vouchers, all zero when unset
+        signer: [u8; 32],
+        /// Voucher nonces already redeemed
+        used_nonces: Mapping<u64, ()>,
+        /// Time from which an allowance counts as zero, absent for allowances that never expire
+        allowance_expiry: Mapping<(AccountId, AccountId), Timestamp>,
+        /// Nonces of signed transfers already executed, per signing account
+        transfer_nonces: Mapping<(AccountId, u64), ()>,
+        /// Set of operations paused on their own, on top of the blanket `paused` flag
+        paused_operations: Mapping<Operation, ()>,
+        /// Smallest amount a non-owner may transfer, unless it empties their balance
+        min_transfer: u128,
+        /// Open swaps by id, their `give` side escrowed in the contract account
+        swaps: Mapping<u32, Swap>,
+        /// Id the next swap will get
+        next_swap_id: u32,
+        /// Remaining mint allowance of capped minters, absent for uncapped ones
+        minter_budgets: Mapping<AccountId, u128>,
+        /// Account offered ownership that has not accepted it yet
+        pending_owner: Option<AccountId>,
+        /// Ids of the snapshots each account has a balance recorded for, in ascending order
+        snapshot_records: Mapping<(AccountId, u32), u32>,
+        /// Number of snapshot ids recorded per account
+        snapshot_record_count: Mapping<AccountId, u32>,
     }
 
     impl Default for Token {
@@ -22,132 +290,4288 @@ mod token {
         /// Constructor that initializes the token contract with empty balances.
         #[ink(constructor)]
         pub fn new() -> Self {
-            Self {
+            Self::new_with_metadata(String::new(), String::new(), 18)
+        }
+
+        /// Constructor that initializes the token contract with the given metadata.
+        /// The caller becomes the owner.
+        #[ink(constructor)]
+        pub fn new_with_metadata(name: String, symbol: String, decimals: u8) -> Self {
+            let mut token = Self {
                 balances: Mapping::new(),
-            }
+                total_supply: 0,
+                allowances: Mapping::new(),
+                name,
+                symbol,
+                decimals,
+                owner: Self::env().caller(),
+                minters: Mapping::new(),
+                cap: u128::MAX,
+                paused: false,
+                holder_count: 0,
+                frozen: Mapping::new(),
+                snapshot_id: 0,
+                snapshot_balances: Mapping::new(),
+                locked: false,
+                fee_bps: 0,
+                fee_collector: Self::env().caller(),
+                vesting: Mapping::new(),
+                merkle_root: [0u8; 32],
+                claimed: Mapping::new(),
+                allow_zero_address: false,
+                total_minted: 0,
+                total_burned: 0,
+                max_balance: u128::MAX,
+                max_balance_exempt: Mapping::new(),
+                locks: Mapping::new(),
+                delegates: Mapping::new(),
+                checkpoints: Mapping::new(),
+                checkpoint_count: Mapping::new(),
+                storage_version: STORAGE_VERSION,
+                reflection_bps: 0,
+                magnified_dividend_per_share: 0,
+                dividend_checkpoints: Mapping::new(),
+                dividends_owed: Mapping::new(),
+                drip_amount: 0,
+                cooldown: 0,
+                last_claim: Mapping::new(),
+                signer: [0u8; 32],
+                used_nonces: Mapping::new(),
+                allowance_expiry: Mapping::new(),
+                transfer_nonces: Mapping::new(),
+                paused_operations: Mapping::new(),
+                min_transfer: 0,
+                swaps: Mapping::new(),
+                next_swap_id: 0,
+                minter_budgets: Mapping::new(),
+                pending_owner: None,
+                snapshot_records: Mapping::new(),
+                snapshot_record_count: Mapping::new(),
+            };
+            // The contract holds reflections until their holders withdraw them
+            token.max_balance_exempt.insert(Self::env().account_id(), &());
+            token
+        }
+
+        /// Constructor that limits the total supply to `cap`.
+        /// A cap of zero means no tokens can ever be minted.
+        #[ink(constructor)]
+        pub fn new_with_cap(cap: u128) -> Self {
+            let mut token = Self::new();
+            token.cap = cap;
+            token
+        }
+
+        /// Constructor that chooses whether the all-zero account may receive tokens.
+        /// By default mints and transfers to it are rejected to avoid losing funds.
+        #[ink(constructor)]
+        pub fn new_with_zero_address(allow_zero_address: bool) -> Self {
+            let mut token = Self::new();
+            token.allow_zero_address = allow_zero_address;
+            token
+        }
+
+        /// Constructor that enables a faucet minting `drip_amount` tokens to any caller,
+        /// at most once per `cooldown` milliseconds per account.
+        #[ink(constructor)]
+        pub fn new_with_faucet(drip_amount: u128, cooldown: u64) -> Self {
+            let mut token = Self::new();
+            token.drip_amount = drip_amount;
+            token.cooldown = cooldown;
+            token
         }
 
         /// Constructor that initializes the token contract with empty balances.
         #[ink(constructor)]
+        #[allow(clippy::should_implement_trait)]
         pub fn default() -> Self {
             Self::new()
         }
 
         /// Mint tokens to a specific account.
         /// Increases the balance of the specified account by the given amount.
+        /// Capped minters spend their remaining budget, while the owner is never capped.
+        /// Returns an error if the contract is paused, if the caller is neither
+        /// the owner nor a minter, if a capped minter's budget is too small, or if
+        /// the new total supply would overflow or exceed the cap.
         #[ink(message)]
-        pub fn mint(&mut self, to: AccountId, amount: u128) {
-            let current_balance = self.balances.get(to).unwrap_or(0);
-            let new_balance = current_balance.checked_add(amount)
-                .expect("Balance overflow");
-            self.balances.insert(to, &new_balance);
+        pub fn mint(&mut self, to: AccountId, amount: u128) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Mint)?;
+            let caller = self.env().caller();
+            if caller == self.owner {
+                return self.mint_tokens(to, amount);
+            }
+            if !self.is_minter(caller) {
+                return Err(Error::NotOwner);
+            }
+
+            let budget = self.minter_budgets.get(caller)
+                .map(|budget| budget.checked_sub(amount).ok_or(Error::MinterCapExceeded))
+                .transpose()?;
+            self.mint_tokens(to, amount)?;
+            if let Some(budget) = budget {
+                self.minter_budgets.insert(caller, &budget);
+            }
+            Ok(())
         }
 
-        /// Get the balance of a specific account.
-        /// Returns 0 if the account has no balance.
+        /// Mint tokens to several accounts at once.
+        /// The summed amount is checked against the cap before anything is applied.
+        /// Returns an error if the caller is not the owner, the vectors differ in
+        /// length, or the new total supply would overflow or exceed the cap.
         #[ink(message)]
-        pub fn balance_of(&self, account: AccountId) -> u128 {
-            self.balances.get(account).unwrap_or(0)
+        pub fn batch_mint(
+            &mut self,
+            recipients: Vec<AccountId>,
+            amounts: Vec<u128>,
+        ) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Mint)?;
+            self.ensure_owner()?;
+            if recipients.len() != amounts.len() {
+                return Err(Error::LengthMismatch);
+            }
+
+            let mut total: u128 = 0;
+            for (to, amount) in recipients.iter().zip(amounts.iter()) {
+                self.ensure_valid_recipient(*to)?;
+                total = total.checked_add(*amount).ok_or(Error::Overflow)?;
+            }
+            let new_supply = self.total_supply.checked_add(total).ok_or(Error::Overflow)?;
+            if new_supply > self.cap {
+                return Err(Error::CapExceeded);
+            }
+            let new_minted = self.total_minted.checked_add(total).ok_or(Error::Overflow)?;
+            // Repeated recipients are checked against the sum of all their credits
+            for to in recipients.iter() {
+                let credit: u128 = recipients.iter().zip(amounts.iter())
+                    .filter(|(account, _)| *account == to)
+                    .map(|(_, amount)| *amount)
+                    .sum();
+                let new_balance = self.balances.get(to).unwrap_or(0) + credit;
+                self.ensure_within_max_balance(*to, new_balance)?;
+            }
+
+            // No balance can exceed the total supply, so the credits below can't overflow
+            for (to, amount) in recipients.into_iter().zip(amounts) {
+                let current_balance = self.balances.get(to).unwrap_or(0);
+                let new_balance = current_balance.checked_add(amount)
+                    .expect("Balance overflow");
+                self.set_balance(to, new_balance);
+                self.env().emit_event(Transfer {
+                    from: None,
+                    to: Some(to),
+                    value: amount,
+                });
+            }
+            self.total_supply = new_supply;
+            self.total_minted = new_minted;
+
+            Ok(())
         }
 
-        /// Transfer tokens from the caller to another account.
-        /// Returns an error if the caller has insufficient balance.
+        /// Get the name of the token.
+        #[ink(message)]
+        pub fn token_name(&self) -> String {
+            self.name.clone()
+        }
+
+        /// Get the symbol of the token.
+        #[ink(message)]
+        pub fn token_symbol(&self) -> String {
+            self.symbol.clone()
+        }
+
+        /// Get the number of decimals used to display balances.
+        #[ink(message)]
+        pub fn token_decimals(&self) -> u8 {
+            self.decimals
+        }
+
+        /// Get the maximum total supply.
+        #[ink(message)]
+        pub fn cap(&self) -> u128 {
+            self.cap
+        }
+
+        /// Get the number of accounts holding a non-zero balance.
+        #[ink(message)]
+        pub fn holder_count(&self) -> u32 {
+            self.holder_count
+        }
+
+        /// Get the cumulative amount of tokens ever minted.
+        #[ink(message)]
+        pub fn total_minted(&self) -> u128 {
+            self.total_minted
+        }
+
+        /// Get the cumulative amount of tokens ever burned.
+        /// The total supply always equals `total_minted - total_burned`.
+        #[ink(message)]
+        pub fn total_burned(&self) -> u128 {
+            self.total_burned
+        }
+
+        /// Transfer tokens from the caller to another account with a payment reference.
+        /// Behaves like `transfer` and additionally emits the memo in an event.
+        #[ink(message)]
+        pub fn transfer_with_memo(
+            &mut self,
+            to: AccountId,
+            amount: u128,
+            memo: [u8; 32],
+        ) -> Result<(), Error> {
+            self.transfer_from_caller(to, amount)?;
+            self.env().emit_event(TransferWithMemo {
+                from: self.env().caller(),
+                to,
+                value: amount,
+                memo,
+            });
+            Ok(())
+        }
+
+        /// Allow `spender` to spend `amount` of the caller's tokens until `expiry`.
+        /// From `expiry` on the allowance counts as zero. `increase_allowance` and
+        /// `decrease_allowance` keep the expiry until it has passed, while `approve`
+        /// removes it again.
+        #[ink(message)]
+        pub fn approve_with_expiry(&mut self, spender: AccountId, amount: u128, expiry: Timestamp) {
+            let owner = self.env().caller();
+            self.set_allowance(owner, spender, amount);
+            self.allowance_expiry.insert((owner, spender), &expiry);
+        }
+
+        /// Get the time from which the allowance of `spender` over `owner`'s tokens
+        /// counts as zero, or `None` if it never expires.
+        #[ink(message)]
+        pub fn allowance_expiry(&self, owner: AccountId, spender: AccountId) -> Option<Timestamp> {
+            self.allowance_expiry.get((owner, spender))
+        }
+
+        /// Allow `spender` to spend any amount of the caller's tokens, like `approve`
+        /// with `UNLIMITED_ALLOWANCE`. Spending never lowers this allowance.
+        #[ink(message)]
+        pub fn approve_max(&mut self, spender: AccountId) {
+            let owner = self.env().caller();
+            self.set_allowance(owner, spender, UNLIMITED_ALLOWANCE);
+            self.allowance_expiry.remove((owner, spender));
+        }
+
+        /// Take away any allowance `spender` has over the caller's tokens, like `approve` with zero.
+        #[ink(message)]
+        pub fn revoke(&mut self, spender: AccountId) {
+            let owner = self.env().caller();
+            self.set_allowance(owner, spender, 0);
+            self.allowance_expiry.remove((owner, spender));
+        }
+
+        /// Transfer `amount` tokens from `from` to `to` on `from`'s signed behalf, so a
+        /// relayer can submit the call and pay for it. `from` signs the SCALE encoded
+        /// `(token, from, to, amount, nonce, deadline)` with the sr25519 key of its
+        /// account, `token` being this contract, so the relayer can't change any of it.
+        /// The transfer fee and reflection are charged to `from`, as with `transfer_from`.
+        /// Returns an error if the contract is paused, `deadline` has passed, `from`
+        /// already used `nonce`, the signature doesn't match or the transfer fails.
+        #[ink(message)]
+        pub fn transfer_with_sig(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            amount: u128,
+            nonce: u64,
+            deadline: Timestamp,
+            signature: [u8; 64],
+        ) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            if deadline < self.env().block_timestamp() {
+                return Err(Error::Expired);
+            }
+            if self.transfer_nonces.contains((from, nonce)) {
+                return Err(Error::NonceUsed);
+            }
+            let message = scale::Encode::encode(&(self.env().account_id(), from, to, amount, nonce, deadline));
+            self.env()
+                .sr25519_verify(&signature, &message, from.as_ref())
+                .map_err(|_| Error::InvalidSignature)?;
+
+            self.transfer_charged(from, to, amount)?;
+            self.transfer_nonces.insert((from, nonce), &());
+            Ok(())
+        }
+
+        /// Allow the `spender` contract to spend `amount` of the caller's tokens, like
+        /// `approve`, and then call its `ApprovalReceiver::on_approval_received` hook.
+        /// The hook may call back into this contract to spend the allowance right away,
+        /// so the contract state is read back from storage once the hook returns.
+        /// Returns an error if `spender` is not a contract or the hook rejects or is
+        /// missing, in which case the approval is reverted too.
+        #[ink(message)]
+        pub fn approve_and_call(&mut self, spender: AccountId, amount: u128, data: Vec<u8>) -> Result<(), Error> {
+            if !self.env().is_contract(&spender) {
+                return Err(Error::CallbackFailed);
+            }
+            let owner = self.env().caller();
+            self.set_allowance(owner, spender, amount);
+            self.allowance_expiry.remove((owner, spender));
+            let result = build_call::<Environment>()
+                .call(spender)
+                .call_flags(CallFlags::ALLOW_REENTRY)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "ApprovalReceiver::on_approval_received"
+                    )))
+                    .push_arg(owner)
+                    .push_arg(amount)
+                    .push_arg(data),
+                )
+                .returns::<Result<(), String>>()
+                .try_invoke();
+            if !matches!(result, Ok(Ok(Ok(())))) {
+                return Err(Error::CallbackFailed);
+            }
+            // Reentrant calls wrote their changes to storage; writing back the copy
+            // loaded before the hook when this message returns would undo them
+            if let Ok(Some(state)) = ink::env::get_contract_storage::<ink::primitives::Key, Self>(
+                &<Self as ink::storage::traits::StorageKey>::KEY,
+            ) {
+                *self = state;
+            }
+            Ok(())
+        }
+
+        /// Check whether `account` already used `nonce` for a signed transfer.
+        #[ink(message)]
+        pub fn is_transfer_nonce_used(&self, account: AccountId, nonce: u64) -> bool {
+            self.transfer_nonces.contains((account, nonce))
+        }
+
+        /// Get the balances of `accounts` in the same order, 0 for accounts without one.
+        /// Only the first `MAX_BALANCE_QUERIES` accounts are looked up; the rest are
+        /// silently dropped, so the result can be shorter than the input.
+        #[ink(message)]
+        pub fn balances_of(&self, accounts: Vec<AccountId>) -> Vec<u128> {
+            accounts
+                .into_iter()
+                .take(MAX_BALANCE_QUERIES)
+                .map(|account| self.balance_of(account))
+                .collect()
+        }
+
+        /// Transfer tokens from the caller like `transfer` and return the resulting
+        /// `(caller_balance, to_balance)`, saving frontends a query afterwards.
+        #[ink(message)]
+        pub fn transfer_returning(&mut self, to: AccountId, amount: u128) -> Result<(u128, u128), Error> {
+            self.transfer_from_caller(to, amount)?;
+            Ok((self.balance_of(self.env().caller()), self.balance_of(to)))
+        }
+
+        /// Transfer tokens from the caller and notify the recipient if it is a contract.
+        /// Contract recipients must implement `TokenReceiver::on_token_received`.
+        /// Returns an error if the transfer fails or the recipient rejects or lacks the hook,
+        /// in which case the whole transfer is reverted.
+        #[ink(message)]
+        pub fn safe_transfer(
+            &mut self,
+            to: AccountId,
+            amount: u128,
+            data: Vec<u8>,
+        ) -> Result<(), Error> {
+            self.transfer_from_caller(to, amount)?;
+            if !self.env().is_contract(&to) {
+                return Ok(());
+            }
+            let result = build_call::<Environment>()
+                .call(to)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "TokenReceiver::on_token_received"
+                    )))
+                    .push_arg(self.env().caller())
+                    .push_arg(amount)
+                    .push_arg(data),
+                )
+                .returns::<Result<(), String>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(Error::TransferRejected),
+            }
+        }
+
+        /// Transfer tokens from the caller to several accounts at once.
+        /// Each leg is charged and checked like `transfer`. The summed amount is checked
+        /// up front, and a leg failing after that returns its error, which reverts the
+        /// legs applied before it.
+        /// Returns an error if the vectors differ in length, the caller can't cover
+        /// the summed amount, or a leg fails.
         #[ink(message)]
-        pub fn transfer(&mut self, to: AccountId, amount: u128) -> Result<(), Error> {
+        pub fn batch_transfer(
+            &mut self,
+            recipients: Vec<AccountId>,
+            amounts: Vec<u128>,
+        ) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            if recipients.len() != amounts.len() {
+                return Err(Error::LengthMismatch);
+            }
+
             let caller = self.env().caller();
-            let caller_balance = self.balances.get(caller).unwrap_or(0);
-            
-            if caller_balance < amount {
+            self.ensure_not_frozen(caller)?;
+            let total = amounts.iter()
+                .try_fold(0u128, |total, amount| total.checked_add(*amount))
+                .ok_or(Error::InsufficientBalance)?;
+            if self.balance_of(caller) < total {
                 return Err(Error::InsufficientBalance);
             }
 
-            let to_balance = self.balances.get(to).unwrap_or(0);
-            
-            let new_caller_balance = caller_balance.checked_sub(amount)
-                .expect("Balance underflow");
-            let new_to_balance = to_balance.checked_add(amount)
-                .expect("Balance overflow");
-            
-            self.balances.insert(caller, &new_caller_balance);
-            self.balances.insert(to, &new_to_balance);
-            
+            for (to, amount) in recipients.into_iter().zip(amounts) {
+                self.transfer_charged(caller, to, amount)?;
+            }
+            Ok(())
+        }
+
+        /// Transfer tokens from the caller to several accounts, best effort.
+        /// Unlike `batch_transfer` this is not atomic: each leg is attempted on its own,
+        /// in order, as if by `transfer`, and the legs that succeed stay applied even when
+        /// others fail. Returns one result per recipient; if the vectors differ in length
+        /// nothing is transferred and every leg reports `Error::LengthMismatch`.
+        #[ink(message)]
+        pub fn best_effort_batch_transfer(
+            &mut self,
+            recipients: Vec<AccountId>,
+            amounts: Vec<u128>,
+        ) -> Vec<Result<(), Error>> {
+            if recipients.len() != amounts.len() {
+                return recipients.iter().map(|_| Err(Error::LengthMismatch)).collect();
+            }
+            recipients
+                .into_iter()
+                .zip(amounts)
+                .map(|(to, amount)| self.transfer_from_caller(to, amount))
+                .collect()
+        }
+
+        /// Burn tokens from the caller's balance.
+        /// Decreases both the caller's balance and the total supply.
+        /// Returns an error if the caller has insufficient balance.
+        #[ink(message)]
+        pub fn burn(&mut self, amount: u128) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.burn_tokens(caller, amount)
+        }
+
+        /// Burn tokens on behalf of `from` using the caller's allowance.
+        /// Decreases the allowance, unless it is `UNLIMITED_ALLOWANCE`, `from`'s balance
+        /// and the total supply.
+        /// Returns an error if the allowance or `from`'s balance is insufficient.
+        #[ink(message)]
+        pub fn burn_from(&mut self, from: AccountId, amount: u128) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let allowance = self.allowance(from, caller);
+
+            if allowance < amount {
+                return Err(Error::InsufficientAllowance);
+            }
+
+            // Burns the balance or fails without writing anything
+            self.burn_tokens(from, amount)?;
+            self.spend_allowance(from, caller, allowance, amount);
+
+            Ok(())
+        }
+
+        /// Mint `amount` tokens to the owner's own balance and emit `SupplyChanged`.
+        /// Returns an error if the caller is not the owner, minting is paused, or the
+        /// new total supply would overflow or exceed the cap.
+        #[ink(message)]
+        pub fn expand_supply(&mut self, amount: u128) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.ensure_not_paused(Operation::Mint)?;
+            self.mint_tokens(self.owner, amount)?;
+            self.env().emit_event(SupplyChanged {
+                new_total: self.total_supply,
+            });
+            Ok(())
+        }
+
+        /// Burn `amount` tokens from the owner's own balance and emit `SupplyChanged`.
+        /// Returns an error if the caller is not the owner or holds too few tokens.
+        #[ink(message)]
+        pub fn contract_supply(&mut self, amount: u128) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.burn_tokens(self.owner, amount)?;
+            self.env().emit_event(SupplyChanged {
+                new_total: self.total_supply,
+            });
+            Ok(())
+        }
+
+        /// Allow `minter` to mint tokens.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn add_minter(&mut self, minter: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.minters.insert(minter, &());
+            Ok(())
+        }
+
+        /// Revoke the minting permission of `minter`.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn remove_minter(&mut self, minter: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.minters.remove(minter);
+            self.minter_budgets.remove(minter);
+            Ok(())
+        }
+
+        /// Cap `minter` to minting `budget` more tokens, or lift the cap with `None`.
+        /// Minters start out uncapped.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_minter_budget(&mut self, minter: AccountId, budget: Option<u128>) -> Result<(), Error> {
+            self.ensure_owner()?;
+            match budget {
+                Some(budget) => {
+                    self.minter_budgets.insert(minter, &budget);
+                }
+                None => self.minter_budgets.remove(minter),
+            }
+            Ok(())
+        }
+
+        /// Add `amount` to the remaining budget of a capped `minter`.
+        /// Uncapped minters stay uncapped.
+        /// Returns an error if the caller is not the owner or the budget would overflow.
+        #[ink(message)]
+        pub fn top_up_minter_budget(&mut self, minter: AccountId, amount: u128) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if let Some(budget) = self.minter_budgets.get(minter) {
+                let budget = budget.checked_add(amount).ok_or(Error::Overflow)?;
+                self.minter_budgets.insert(minter, &budget);
+            }
+            Ok(())
+        }
+
+        /// Get how many more tokens `minter` may mint, or `None` if it is uncapped.
+        #[ink(message)]
+        pub fn minter_budget(&self, minter: AccountId) -> Option<u128> {
+            self.minter_budgets.get(minter)
+        }
+
+        /// Check whether `who` is in the minter set.
+        /// The owner can always mint but is not reported here unless added.
+        #[ink(message)]
+        pub fn is_minter(&self, who: AccountId) -> bool {
+            self.minters.contains(who)
+        }
+
+        /// Freeze all transfers and mints.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.paused = true;
+            self.env().emit_event(Paused {
+                account: self.env().caller(),
+            });
             Ok(())
         }
-    }
 
-    /// Custom error types for the token contract.
-    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum Error {
-        InsufficientBalance,
-    }
+        /// Resume transfers and mints.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.paused = false;
+            self.env().emit_event(Unpaused {
+                account: self.env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Check whether token movement is currently frozen.
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Pause or resume just the messages grouped under `op`, leaving the others usable.
+        /// `pause` still freezes everything regardless of these flags.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_operation_paused(&mut self, op: Operation, paused: bool) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if paused {
+                self.paused_operations.insert(op, &());
+            } else {
+                self.paused_operations.remove(op);
+            }
+            Ok(())
+        }
+
+        /// Check whether `op` is paused, either on its own or by the blanket `pause`.
+        #[ink(message)]
+        pub fn is_operation_paused(&self, op: Operation) -> bool {
+            self.paused || self.paused_operations.contains(op)
+        }
+
+        /// Block `account` from sending or receiving tokens.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn freeze(&mut self, account: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.frozen.insert(account, &());
+            Ok(())
+        }
+
+        /// Allow a frozen `account` to send and receive tokens again.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn unfreeze(&mut self, account: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.frozen.remove(account);
+            Ok(())
+        }
+
+        /// Check whether `account` is frozen.
+        #[ink(message)]
+        pub fn is_frozen(&self, account: AccountId) -> bool {
+            self.frozen.contains(account)
+        }
+
+        /// Take a snapshot of all balances and return its id.
+        /// Nothing is copied here: an account's balance is only recorded under the
+        /// latest snapshot id right before it next changes.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn snapshot(&mut self) -> Result<u32, Error> {
+            self.ensure_owner()?;
+            self.snapshot_id = self.snapshot_id.checked_add(1)
+                .ok_or(Error::Overflow)?;
+            Ok(self.snapshot_id)
+        }
+
+        /// Get the id of the latest snapshot, 0 if none has been taken.
+        #[ink(message)]
+        pub fn current_snapshot_id(&self) -> u32 {
+            self.snapshot_id
+        }
+
+        /// Get the balance `account` held when snapshot `snapshot_id` was taken.
+        /// The first balance recorded at or after `snapshot_id` is the one that was
+        /// current at that snapshot; if none exists the balance hasn't changed since.
+        /// Returns `None` for snapshot ids that haven't been taken.
+        #[ink(message)]
+        pub fn balance_of_at(&self, account: AccountId, snapshot_id: u32) -> Option<u128> {
+            if snapshot_id == 0 || snapshot_id > self.snapshot_id {
+                return None;
+            }
+
+            // Binary search for the number of records made before `snapshot_id`
+            let count = self.snapshot_record_count.get(account).unwrap_or(0);
+            let mut low = 0;
+            let mut high = count;
+            while low < high {
+                let mid = low + (high - low) / 2;
+                let id = self.snapshot_records.get((account, mid))
+                    .expect("Snapshot record missing");
+                if id < snapshot_id {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            if low == count {
+                return Some(self.balance_of(account));
+            }
+            let id = self.snapshot_records.get((account, low)).expect("Snapshot record missing");
+            self.snapshot_balances.get((id, account))
+        }
+
+        /// Configure the transfer fee in basis points and the account receiving it.
+        /// Returns an error if the caller is not the owner, `bps` plus the
+        /// reflection exceeds 10000 or `collector` is the zero account, even where
+        /// the zero account may otherwise receive tokens.
+        #[ink(message)]
+        pub fn set_fee(&mut self, bps: u16, collector: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if bps > 10_000 - self.reflection_bps {
+                return Err(Error::InvalidFee);
+            }
+            if collector == AccountId::from([0u8; 32]) {
+                return Err(Error::ZeroAddress);
+            }
+            self.fee_bps = bps;
+            self.fee_collector = collector;
+            Ok(())
+        }
+
+        /// Get the transfer fee in basis points.
+        #[ink(message)]
+        pub fn fee_bps(&self) -> u16 {
+            self.fee_bps
+        }
+
+        /// Get the account receiving transfer fees.
+        #[ink(message)]
+        pub fn fee_collector(&self) -> AccountId {
+            self.fee_collector
+        }
+
+        /// Configure the reflection in basis points skimmed from every transfer and
+        /// shared among all holders in proportion to their balances.
+        /// Returns an error if the caller is not the owner or `bps` plus the fee
+        /// exceeds 10000.
+        #[ink(message)]
+        pub fn set_reflection(&mut self, bps: u16) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if bps > 10_000 - self.fee_bps {
+                return Err(Error::InvalidFee);
+            }
+            self.reflection_bps = bps;
+            Ok(())
+        }
+
+        /// Get the reflection in basis points.
+        #[ink(message)]
+        pub fn reflection_bps(&self) -> u16 {
+            self.reflection_bps
+        }
+
+        /// Get the reflections `account` has earned and not yet withdrawn.
+        /// Until withdrawn they are held by the contract account.
+        #[ink(message)]
+        pub fn withdrawable_dividend(&self, account: AccountId) -> u128 {
+            if account == self.env().account_id() {
+                return 0;
+            }
+            let per_share = self.magnified_dividend_per_share
+                - self.dividend_checkpoints.get(account).unwrap_or(0);
+            let accrued = demagnify(self.balance_of(account), per_share);
+            self.dividends_owed.get(account).unwrap_or(0).saturating_add(accrued)
+        }
+
+        /// Share `amount` of the caller's tokens among all holders by their balances now,
+        /// for example revenue bought back with this token. Holders withdraw their share
+        /// with `withdraw_dividend`, alongside their reflections. The caller's remaining
+        /// balance takes part in the distribution like any other holding.
+        /// Returns an error if the caller is not the owner, can't cover `amount`, or
+        /// would be depositing to nobody.
+        #[ink(message)]
+        pub fn deposit_dividends(&mut self, amount: u128) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.ensure_not_paused(Operation::Transfer)?;
+            if amount == 0 {
+                return Ok(());
+            }
+            let per_share = self.per_share_after(amount)?.ok_or(Error::NoHolders)?;
+            let caller = self.env().caller();
+            self.transfer_from_to(caller, self.env().account_id(), amount)?;
+            self.magnified_dividend_per_share = per_share;
+            Ok(())
+        }
+
+        /// Move the caller's earned reflections from the contract account to the caller.
+        /// Returns the amount withdrawn.
+        #[ink(message)]
+        pub fn withdraw_dividend(&mut self) -> Result<u128, Error> {
+            let caller = self.env().caller();
+            let amount = self.withdrawable_dividend(caller);
+            if amount == 0 {
+                return Ok(0);
+            }
+            // Crediting the caller settles everything earned so far into `dividends_owed`
+            self.transfer_from_to(self.env().account_id(), caller, amount)?;
+            self.dividends_owed.remove(caller);
+            Ok(amount)
+        }
+
+        /// Grant `beneficiary` `total` tokens unlocking linearly over `duration`
+        /// milliseconds, starting now. Tokens are minted as they are claimed.
+        /// Returns an error if the caller is not the owner or the beneficiary
+        /// already has a schedule.
+        #[ink(message)]
+        pub fn create_vesting(
+            &mut self,
+            beneficiary: AccountId,
+            total: u128,
+            duration: u64,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if self.vesting.contains(beneficiary) {
+                return Err(Error::VestingExists);
+            }
+            let schedule = VestingSchedule {
+                total,
+                start: self.env().block_timestamp(),
+                duration,
+                claimed: 0,
+            };
+            self.vesting.insert(beneficiary, &schedule);
+            Ok(())
+        }
+
+        /// Get the vesting schedule of `beneficiary`, if any.
+        #[ink(message)]
+        pub fn vesting_of(&self, beneficiary: AccountId) -> Option<VestingSchedule> {
+            self.vesting.get(beneficiary)
+        }
+
+        /// Mint the portion of the caller's schedule unlocked since the last claim
+        /// and return the amount claimed.
+        /// Returns an error if the caller has no vesting schedule.
+        #[ink(message)]
+        pub fn claim_vested(&mut self) -> Result<u128, Error> {
+            self.ensure_not_paused(Operation::Mint)?;
+            let caller = self.env().caller();
+            let mut schedule = self.vesting.get(caller).ok_or(Error::NoVestingSchedule)?;
+
+            let elapsed = self.env().block_timestamp().saturating_sub(schedule.start);
+            let unlocked = if elapsed >= schedule.duration {
+                schedule.total
+            } else {
+                // Split the multiplication so large totals can't overflow
+                let duration = u128::from(schedule.duration);
+                let elapsed = u128::from(elapsed);
+                schedule.total / duration * elapsed
+                    + schedule.total % duration * elapsed / duration
+            };
+            let claimable = unlocked.saturating_sub(schedule.claimed);
+            if claimable == 0 {
+                return Ok(0);
+            }
+
+            self.mint_tokens(caller, claimable)?;
+            schedule.claimed = unlocked;
+            self.vesting.insert(caller, &schedule);
+
+            Ok(claimable)
+        }
+
+        /// Set the merkle root of the airdrop.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_merkle_root(&mut self, root: [u8; 32]) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.merkle_root = root;
+            Ok(())
+        }
+
+        /// Get the merkle root of the airdrop.
+        #[ink(message)]
+        pub fn merkle_root(&self) -> [u8; 32] {
+            self.merkle_root
+        }
+
+        /// Check whether `account` already claimed its airdrop.
+        #[ink(message)]
+        pub fn is_claimed(&self, account: AccountId) -> bool {
+            self.claimed.contains(account)
+        }
+
+        /// Claim `amount` airdropped tokens by proving `(caller, amount)` is in the tree.
+        /// Leaves are the Blake2x256 hash of the SCALE encoded `(AccountId, u128)` and
+        /// each node hashes the encoded pair of its children, smaller hash first.
+        /// Returns an error if the caller already claimed or the proof is invalid.
+        #[ink(message)]
+        pub fn claim(&mut self, amount: u128, proof: Vec<[u8; 32]>) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Mint)?;
+            let caller = self.env().caller();
+            if self.is_claimed(caller) {
+                return Err(Error::AlreadyClaimed);
+            }
+
+            let leaf = self.env().hash_encoded::<Blake2x256, _>(&(caller, amount));
+            let root = proof.iter().fold(leaf, |node, sibling| {
+                if node <= *sibling {
+                    self.env().hash_encoded::<Blake2x256, _>(&(node, *sibling))
+                } else {
+                    self.env().hash_encoded::<Blake2x256, _>(&(*sibling, node))
+                }
+            });
+            if root != self.merkle_root {
+                return Err(Error::InvalidProof);
+            }
+
+            self.mint_tokens(caller, amount)?;
+            self.claimed.insert(caller, &());
+            Ok(())
+        }
+
+        /// Wrap the native value sent with the call into as many tokens for the caller.
+        /// Together with `withdraw` this keeps the total supply equal to the contract's
+        /// native balance, as long as tokens are not also minted through other means.
+        /// Returns an error if the contract is paused or the tokens can't be minted.
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Mint)?;
+            let caller = self.env().caller();
+            self.mint_tokens(caller, self.env().transferred_value())
+        }
+
+        /// Unwrap `amount` of the caller's tokens, burning them and sending the caller
+        /// the same native value.
+        /// Returns an error if the contract is paused, the caller's balance is insufficient
+        /// or the native transfer fails.
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: u128) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            let caller = self.env().caller();
+            self.burn_tokens(caller, amount)?;
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| Error::NativeTransferFailed)
+        }
+
+        /// Get the account of this contract, which holds wrapped native value, escrowed
+        /// swaps and reflections.
+        #[ink(message)]
+        pub fn contract_account(&self) -> AccountId {
+            self.env().account_id()
+        }
+
+        /// Get the native balance of this contract, which backs tokens minted by `deposit`.
+        #[ink(message)]
+        pub fn contract_balance(&self) -> Balance {
+            self.env().balance()
+        }
+
+        /// Send `amount` of the contract's native balance to `to`, as an escape hatch for
+        /// funds held by `deposit`. Only possible while the contract is paused, so the
+        /// backing of wrapped tokens can't be drained during normal operation.
+        /// Returns an error if the caller is not the owner, the contract is not paused
+        /// or the native transfer fails.
+        #[ink(message)]
+        pub fn emergency_withdraw(&mut self, to: AccountId, amount: u128) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if !self.paused {
+                return Err(Error::NotPaused);
+            }
+            self.env()
+                .transfer(to, amount)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            self.env().emit_event(EmergencyWithdrawal { to, amount });
+            Ok(())
+        }
+
+        /// Mint `drip_amount` tokens to the caller.
+        /// Returns an error if the faucet is disabled, the contract is paused or the
+        /// caller claimed less than `cooldown` milliseconds ago.
+        #[ink(message)]
+        pub fn faucet(&mut self) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Mint)?;
+            if self.drip_amount == 0 {
+                return Err(Error::FaucetDisabled);
+            }
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+            if let Some(last_claim) = self.last_claim.get(caller) {
+                if now.saturating_sub(last_claim) < self.cooldown {
+                    return Err(Error::CooldownActive);
+                }
+            }
+
+            self.mint_tokens(caller, self.drip_amount)?;
+            self.last_claim.insert(caller, &now);
+            Ok(())
+        }
+
+        /// Get the amount minted per faucet claim.
+        #[ink(message)]
+        pub fn drip_amount(&self) -> u128 {
+            self.drip_amount
+        }
+
+        /// Get the faucet cooldown in milliseconds.
+        #[ink(message)]
+        pub fn cooldown(&self) -> u64 {
+            self.cooldown
+        }
+
+        /// Set the sr25519 public key whose signatures mint vouchers are checked against.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_signer(&mut self, signer: [u8; 32]) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.signer = signer;
+            Ok(())
+        }
+
+        /// Get the sr25519 public key signing mint vouchers.
+        #[ink(message)]
+        pub fn signer(&self) -> [u8; 32] {
+            self.signer
+        }
+
+        /// Check whether the voucher `nonce` has been redeemed.
+        #[ink(message)]
+        pub fn is_nonce_used(&self, nonce: u64) -> bool {
+            self.used_nonces.contains(nonce)
+        }
+
+        /// Mint `amount` tokens to the caller against a voucher from the signer.
+        /// The signer signs the SCALE encoded `(token, caller, amount, nonce)` with
+        /// sr25519, where `token` is this contract's account, and each nonce can be
+        /// redeemed once. Verification uses the unstable sr25519
+        /// host function, which the chain must enable.
+        /// Returns an error if the nonce was used or the signature doesn't match.
+        #[ink(message)]
+        pub fn redeem_voucher(
+            &mut self,
+            amount: u128,
+            nonce: u64,
+            signature: [u8; 64],
+        ) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Mint)?;
+            if self.is_nonce_used(nonce) {
+                return Err(Error::NonceUsed);
+            }
+            if self.signer == [0u8; 32] {
+                return Err(Error::InvalidSignature);
+            }
+            let caller = self.env().caller();
+            let message = scale::Encode::encode(&(self.env().account_id(), caller, amount, nonce));
+            self.env()
+                .sr25519_verify(&signature, &message, &self.signer)
+                .map_err(|_| Error::InvalidSignature)?;
+
+            self.mint_tokens(caller, amount)?;
+            self.used_nonces.insert(nonce, &());
+            Ok(())
+        }
+
+        /// Offer `counterparty` to swap `give` of the caller's tokens for `want_amount` of
+        /// the PSP22 `want_token`, which may be this contract. The tokens are escrowed in
+        /// the contract account, where they earn no reflections or dividends, until the
+        /// swap is accepted or cancelled. It can be accepted for `SWAP_DURATION`.
+        /// Returns the swap id, or an error if the caller can't cover `give`.
+        #[ink(message)]
+        pub fn create_swap(
+            &mut self,
+            counterparty: AccountId,
+            give: u128,
+            want_token: AccountId,
+            want_amount: u128,
+        ) -> Result<u32, Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            let maker = self.env().caller();
+            let id = self.next_swap_id;
+            let next_swap_id = id.checked_add(1).ok_or(Error::Overflow)?;
+            self.transfer_from_to(maker, self.env().account_id(), give)?;
+            self.swaps.insert(id, &Swap {
+                maker,
+                counterparty,
+                give,
+                want_token,
+                want_amount,
+                expires_at: self.env().block_timestamp().saturating_add(SWAP_DURATION),
+            });
+            self.next_swap_id = next_swap_id;
+            Ok(id)
+        }
+
+        /// Accept swap `id`, paying its maker `want_amount` of `want_token` and receiving
+        /// the escrowed tokens. Paying in this token is charged like `transfer`. For a
+        /// foreign `want_token` the caller must have approved this contract to spend
+        /// `want_amount` of it first.
+        /// Returns an error if the caller isn't the counterparty of an open swap `id`,
+        /// the swap expired, or either side can't be paid, in which case nothing moves.
+        #[ink(message)]
+        pub fn accept_swap(&mut self, id: u32) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            let caller = self.env().caller();
+            let swap = self.swaps.get(id)
+                .filter(|swap| swap.counterparty == caller)
+                .ok_or(Error::SwapNotFound)?;
+            if self.env().block_timestamp() >= swap.expires_at {
+                return Err(Error::SwapExpired);
+            }
+
+            let escrow = self.env().account_id();
+            if swap.want_token == escrow {
+                self.transfer_charged(caller, swap.maker, swap.want_amount)?;
+            }
+            self.swaps.remove(id);
+            self.transfer_from_to(escrow, caller, swap.give)?;
+            if swap.want_token == escrow {
+                return Ok(());
+            }
+            let result = build_call::<Environment>()
+                .call(swap.want_token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer_from")))
+                        .push_arg(caller)
+                        .push_arg(swap.maker)
+                        .push_arg(swap.want_amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(Error::SwapFailed),
+            }
+        }
+
+        /// Cancel swap `id` and return the escrowed tokens to its maker, also after it expired.
+        /// Returns an error if the caller isn't the maker of an open swap `id`.
+        #[ink(message)]
+        pub fn cancel_swap(&mut self, id: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let swap = self.swaps.get(id)
+                .filter(|swap| swap.maker == caller)
+                .ok_or(Error::SwapNotFound)?;
+            self.transfer_from_to(self.env().account_id(), caller, swap.give)?;
+            self.swaps.remove(id);
+            Ok(())
+        }
+
+        /// Get the open swap `id`, if any.
+        #[ink(message)]
+        pub fn swap(&self, id: u32) -> Option<Swap> {
+            self.swaps.get(id)
+        }
+
+        /// Move `amount` of another PSP22 `token` held by this contract's account to `to`,
+        /// for tokens sent here by mistake.
+        /// Returns an error if the caller is not the owner, `token` is this contract,
+        /// whose own balances are only moved by its holders, or the foreign transfer fails.
+        #[ink(message)]
+        pub fn rescue_tokens(&mut self, token: AccountId, to: AccountId, amount: u128) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if token == self.env().account_id() {
+                return Err(Error::CannotRescueOwnToken);
+            }
+            let result = build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                        .push_arg(to)
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(Error::RescueFailed),
+            }
+        }
+
+        /// Replace the contract code with the already uploaded `code_hash`, keeping storage.
+        /// To upgrade, upload the new code, then call this with its hash; the next call
+        /// runs the new code. The new code must keep this `Token` storage layout, with the
+        /// same fields in the same order and new fields only appended, or existing storage
+        /// will fail to decode and the contract becomes unusable.
+        /// Returns an error if the caller is not the owner or the code hash is unknown.
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: Hash) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.env().set_code_hash(&code_hash).map_err(|_| Error::UpgradeFailed)
+        }
+
+        /// Get the layout version of the data in storage.
+        #[ink(message)]
+        pub fn version(&self) -> u16 {
+            self.storage_version
+        }
+
+        /// Bring storage written by older code up to `STORAGE_VERSION` after `set_code`.
+        /// There is nothing to convert yet; future upgrades add their steps here.
+        /// Returns an error if the caller is not the owner or storage is already current.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if self.storage_version >= STORAGE_VERSION {
+                return Err(Error::AlreadyMigrated);
+            }
+            self.storage_version = STORAGE_VERSION;
+            Ok(())
+        }
+
+        /// Set the largest balance a single account may hold.
+        /// Exempt accounts, which include the owner and fee collector, can exceed it.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_max_balance(&mut self, max_balance: u128) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.max_balance = max_balance;
+            Ok(())
+        }
+
+        /// Get the largest balance a single non-exempt account may hold.
+        #[ink(message)]
+        pub fn max_balance(&self) -> u128 {
+            self.max_balance
+        }
+
+        /// Set the smallest amount a transfer may move, to keep dust out of balances.
+        /// This covers every transfer path, including each leg of a batch and locked transfers.
+        /// Transfers of an account's whole balance and transfers from the owner are exempt.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_min_transfer(&mut self, min_transfer: u128) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.min_transfer = min_transfer;
+            Ok(())
+        }
+
+        /// Get the smallest amount a non-exempt transfer may move.
+        #[ink(message)]
+        pub fn min_transfer(&self) -> u128 {
+            self.min_transfer
+        }
+
+        /// Add `account` to or remove it from the maximum balance exemptions.
+        /// The owner and fee collector are exempt for as long as they hold the role,
+        /// whether or not they are added here.
+        /// Returns an error if the caller is not the owner.
+        #[ink(message)]
+        pub fn set_max_balance_exempt(&mut self, account: AccountId, exempt: bool) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if exempt {
+                self.max_balance_exempt.insert(account, &());
+            } else {
+                self.max_balance_exempt.remove(account);
+            }
+            Ok(())
+        }
+
+        /// Check whether `account` is exempt from the maximum balance.
+        #[ink(message)]
+        pub fn is_max_balance_exempt(&self, account: AccountId) -> bool {
+            (account == self.owner && account != ownable::RENOUNCED)
+                || account == self.fee_collector
+                || self.max_balance_exempt.contains(account)
+        }
+
+        /// Move tokens from the caller into a lock for `to` that matures at `unlock_at`.
+        /// The tokens don't count towards the recipient's balance until released, but
+        /// they do count towards its maximum balance.
+        /// The transfer fee and reflection are charged as with `transfer`, and only the
+        /// rest is locked.
+        /// Returns an error if the caller can't cover `amount` or `to` can't receive it,
+        /// including once `to` holds `MAX_LOCKS` pending locks, if nothing is left to
+        /// lock after the fees or if `unlock_at` is more than `MAX_LOCK_DURATION` away.
+        #[ink(message)]
+        pub fn transfer_locked(
+            &mut self,
+            to: AccountId,
+            amount: u128,
+            unlock_at: Timestamp,
+        ) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            let caller = self.env().caller();
+            self.charge_transfer(caller, amount, |token, net| token.lock_tokens(caller, to, net, unlock_at))
+        }
+
+        /// Move the caller's matured locks into its balance and return the amount released.
+        /// Returns an error if the caller is frozen.
+        #[ink(message)]
+        pub fn release_locks(&mut self) -> Result<u128, Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            let caller = self.env().caller();
+            self.ensure_not_frozen(caller)?;
+            let now = self.env().block_timestamp();
+            let (matured, pending): (Vec<Lock>, Vec<Lock>) = self.locks.get(caller)
+                .unwrap_or_default()
+                .into_iter()
+                .partition(|lock| lock.unlock_at <= now);
+            // Locked tokens are part of the total supply, so this can't overflow
+            let released: u128 = matured.iter().map(|lock| lock.amount).sum();
+            if released == 0 {
+                return Ok(0);
+            }
+
+            let new_balance = self.balance_of(caller) + released;
+            self.set_balance(caller, new_balance);
+            if pending.is_empty() {
+                self.locks.remove(caller);
+            } else {
+                self.locks.insert(caller, &pending);
+            }
+            Ok(released)
+        }
+
+        /// Send the caller's pending locks from `from` back to `from` and return the amount
+        /// returned, so a recipient can clear locks it doesn't want.
+        /// Returns an error if either account is frozen or `from` can't receive the tokens.
+        #[ink(message)]
+        pub fn reject_locks(&mut self, from: AccountId) -> Result<u128, Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            let caller = self.env().caller();
+            self.ensure_not_frozen(caller)?;
+            self.ensure_not_frozen(from)?;
+            let (rejected, kept): (Vec<Lock>, Vec<Lock>) = self.locks.get(caller)
+                .unwrap_or_default()
+                .into_iter()
+                .partition(|lock| lock.from == from);
+            // Locked tokens are part of the total supply, so this can't overflow
+            let returned: u128 = rejected.iter().map(|lock| lock.amount).sum();
+            if returned == 0 {
+                return Ok(0);
+            }
+            let new_balance = self.balance_of(from) + returned;
+            if from != caller {
+                self.ensure_within_max_balance(from, new_balance)?;
+            }
+
+            self.set_balance(from, new_balance);
+            if kept.is_empty() {
+                self.locks.remove(caller);
+            } else {
+                self.locks.insert(caller, &kept);
+            }
+            self.env().emit_event(Transfer {
+                from: Some(caller),
+                to: Some(from),
+                value: returned,
+            });
+            Ok(returned)
+        }
+
+        /// Get the amount of tokens locked for `account` that hasn't been released yet.
+        #[ink(message)]
+        pub fn locked_balance_of(&self, account: AccountId) -> u128 {
+            self.locks.get(account)
+                .unwrap_or_default()
+                .iter()
+                .map(|lock| lock.amount)
+                .sum()
+        }
+
+        /// Delegate the caller's voting power to `to`, which may be the caller itself.
+        /// Balances only carry voting power once delegated.
+        #[ink(message)]
+        pub fn delegate(&mut self, to: AccountId) {
+            let caller = self.env().caller();
+            let from_delegate = self.delegates(caller);
+            self.delegates.insert(caller, &to);
+            self.move_votes(from_delegate, Some(to), self.balance_of(caller));
+            self.env().emit_event(DelegateChanged {
+                delegator: caller,
+                from_delegate,
+                to_delegate: to,
+            });
+        }
+
+        /// Get the account `account` has delegated its voting power to, if any.
+        #[ink(message)]
+        pub fn delegates(&self, account: AccountId) -> Option<AccountId> {
+            self.delegates.get(account)
+        }
+
+        /// Get the current voting power of `account`.
+        #[ink(message)]
+        pub fn get_votes(&self, account: AccountId) -> u128 {
+            match self.checkpoint_count.get(account).unwrap_or(0) {
+                0 => 0,
+                count => self.checkpoints.get((account, count - 1)).map_or(0, |(_, votes)| votes),
+            }
+        }
+
+        /// Get the voting power `account` had at the end of `block`.
+        /// Returns an error if `block` is not yet in the past, as its votes can still change.
+        #[ink(message)]
+        pub fn get_past_votes(&self, account: AccountId, block: BlockNumber) -> Result<u128, Error> {
+            if block >= self.env().block_number() {
+                return Err(Error::BlockNotYetMined);
+            }
+
+            // Binary search for the number of checkpoints written at or before `block`
+            let mut low = 0;
+            let mut high = self.checkpoint_count.get(account).unwrap_or(0);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                let (checkpoint_block, _) = self.checkpoints.get((account, mid))
+                    .expect("Checkpoint missing");
+                if checkpoint_block <= block {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            if low == 0 {
+                return Ok(0);
+            }
+            Ok(self.checkpoints.get((account, low - 1)).map_or(0, |(_, votes)| votes))
+        }
+
+        /// Add `amount` newly minted tokens to `to` and the total supply.
+        /// All checks happen before any storage is written.
+        fn mint_tokens(&mut self, to: AccountId, amount: u128) -> Result<(), Error> {
+            self.ensure_valid_recipient(to)?;
+            let new_supply = self.total_supply.checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            if new_supply > self.cap {
+                return Err(Error::CapExceeded);
+            }
+            let new_minted = self.total_minted.checked_add(amount)
+                .ok_or(Error::Overflow)?;
+
+            let current_balance = self.balances.get(to).unwrap_or(0);
+            let new_balance = current_balance.checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            self.ensure_within_max_balance(to, new_balance)?;
+            self.set_balance(to, new_balance);
+            self.total_supply = new_supply;
+            self.total_minted = new_minted;
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(to),
+                value: amount,
+            });
+
+            Ok(())
+        }
+
+        /// Remove `amount` tokens from `from` and the total supply.
+        /// All checks happen before any storage is written.
+        fn burn_tokens(&mut self, from: AccountId, amount: u128) -> Result<(), Error> {
+            self.ensure_not_frozen(from)?;
+            let from_balance = self.balances.get(from).unwrap_or(0);
+
+            let new_from_balance = from_balance.checked_sub(amount)
+                .ok_or(Error::InsufficientBalance)?;
+            let new_supply = self.total_supply.checked_sub(amount)
+                .expect("Supply underflow");
+
+            self.set_balance(from, new_from_balance);
+            self.total_supply = new_supply;
+            // Nothing can be burned that wasn't minted, so this can't overflow
+            self.total_burned += amount;
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: None,
+                value: amount,
+            });
+
+            Ok(())
+        }
+
+        /// Store the balance of `account` and keep the holder count and snapshots in sync.
+        /// Zero balances are removed from storage rather than stored.
+        fn set_balance(&mut self, account: AccountId, new_balance: u128) {
+            self.settle_dividend(account);
+
+            // Preserve the balance as of the latest snapshot before its first change
+            if self.snapshot_id > 0 && !self.snapshot_balances.contains((self.snapshot_id, account)) {
+                let old_balance = self.balance_of(account);
+                self.snapshot_balances.insert((self.snapshot_id, account), &old_balance);
+                let count = self.snapshot_record_count.get(account).unwrap_or(0);
+                self.snapshot_records.insert((account, count), &self.snapshot_id);
+                self.snapshot_record_count.insert(account, &(count + 1));
+            }
+
+            // Credit or debit the change to the voting power of the account's delegate
+            let old_balance = self.balance_of(account);
+            let delegate = self.delegates(account);
+            if new_balance > old_balance {
+                self.move_votes(None, delegate, new_balance - old_balance);
+            } else {
+                self.move_votes(delegate, None, old_balance - new_balance);
+            }
+
+            let was_holder = self.balances.contains(account);
+            if new_balance == 0 {
+                self.balances.remove(account);
+                if was_holder {
+                    self.holder_count = self.holder_count.checked_sub(1)
+                        .expect("Holder count underflow");
+                }
+            } else {
+                self.balances.insert(account, &new_balance);
+                if !was_holder {
+                    self.holder_count = self.holder_count.checked_add(1)
+                        .expect("Holder count overflow");
+                }
+            }
+        }
+
+        /// Add the reflections earned on the current balance of `account` to what it is
+        /// owed. Must run before every balance change.
+        fn settle_dividend(&mut self, account: AccountId) {
+            if account == self.env().account_id() {
+                return;
+            }
+            let checkpoint = self.dividend_checkpoints.get(account).unwrap_or(0);
+            if checkpoint == self.magnified_dividend_per_share {
+                return;
+            }
+            let per_share = self.magnified_dividend_per_share - checkpoint;
+            let accrued = demagnify(self.balance_of(account), per_share);
+            if accrued > 0 {
+                let owed = self.dividends_owed.get(account).unwrap_or(0);
+                self.dividends_owed.insert(account, &owed.saturating_add(accrued));
+            }
+            self.dividend_checkpoints.insert(account, &self.magnified_dividend_per_share);
+        }
+
+        /// Move `amount` voting power between delegates, `None` meaning no delegate,
+        /// and checkpoint every delegate whose votes changed.
+        fn move_votes(&mut self, from: Option<AccountId>, to: Option<AccountId>, amount: u128) {
+            if from == to || amount == 0 {
+                return;
+            }
+            if let Some(from) = from {
+                let votes = self.get_votes(from).checked_sub(amount)
+                    .expect("Votes underflow");
+                self.write_checkpoint(from, votes);
+            }
+            if let Some(to) = to {
+                // No delegate can hold more votes than the total supply
+                let votes = self.get_votes(to) + amount;
+                self.write_checkpoint(to, votes);
+            }
+        }
+
+        /// Record `votes` for `delegate` at the current block, replacing a checkpoint
+        /// already written in this block.
+        fn write_checkpoint(&mut self, delegate: AccountId, votes: u128) {
+            let block = self.env().block_number();
+            let count = self.checkpoint_count.get(delegate).unwrap_or(0);
+            if count > 0 {
+                if let Some((last_block, _)) = self.checkpoints.get((delegate, count - 1)) {
+                    if last_block == block {
+                        self.checkpoints.insert((delegate, count - 1), &(block, votes));
+                        return;
+                    }
+                }
+            }
+            self.checkpoints.insert((delegate, count), &(block, votes));
+            self.checkpoint_count.insert(delegate, &(count + 1));
+        }
+
+        /// Run `f` while holding the reentrancy lock.
+        /// Returns an error without running `f` if the lock is already held.
+        fn non_reentrant<T>(
+            &mut self,
+            f: impl FnOnce(&mut Self) -> Result<T, Error>,
+        ) -> Result<T, Error> {
+            if self.locked {
+                return Err(Error::Reentrancy);
+            }
+            self.locked = true;
+            let result = f(self);
+            self.locked = false;
+            result
+        }
+
+        /// Returns an error if the caller is not the owner.
+        fn ensure_owner(&self) -> Result<(), Error> {
+            Ok(ownable::only_owner(self.env().caller(), self.owner)?)
+        }
+
+        /// Returns an error if the contract or `op` is paused.
+        fn ensure_not_paused(&self, op: Operation) -> Result<(), Error> {
+            if self.is_operation_paused(op) {
+                return Err(Error::Paused);
+            }
+            Ok(())
+        }
+
+        /// Returns an error if `account` is frozen.
+        fn ensure_not_frozen(&self, account: AccountId) -> Result<(), Error> {
+            if self.is_frozen(account) {
+                return Err(Error::AccountFrozen);
+            }
+            Ok(())
+        }
+
+        /// Returns an error if `to` is the zero account and that isn't allowed.
+        fn ensure_valid_recipient(&self, to: AccountId) -> Result<(), Error> {
+            if !self.allow_zero_address && to == AccountId::from([0u8; 32]) {
+                return Err(Error::ZeroAddress);
+            }
+            Ok(())
+        }
+
+        /// Returns an error if a non-exempt `account` would hold more than the maximum balance.
+        /// Its pending locks count towards the maximum, since they are released into its balance.
+        fn ensure_within_max_balance(&self, account: AccountId, new_balance: u128) -> Result<(), Error> {
+            if self.max_balance == u128::MAX || self.is_max_balance_exempt(account) {
+                return Ok(());
+            }
+            if new_balance.saturating_add(self.locked_balance_of(account)) > self.max_balance {
+                return Err(Error::MaxBalanceExceeded);
+            }
+            Ok(())
+        }
+
+        /// Returns an error if `from` moving `amount` would be a dust transfer.
+        fn ensure_above_minimum(&self, from: AccountId, amount: u128) -> Result<(), Error> {
+            if amount < self.min_transfer && from != self.owner && amount != self.balance_of(from) {
+                return Err(Error::BelowMinimum);
+            }
+            Ok(())
+        }
+
+        /// Replace the owner and emit the corresponding event.
+        fn set_owner(&mut self, new_owner: AccountId) {
+            let previous_owner = self.owner;
+            self.owner = new_owner;
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            });
+        }
+
+        /// Move `amount` tokens from the caller to `to`, charging the transfer fee and reflection.
+        fn transfer_from_caller(&mut self, to: AccountId, amount: u128) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            self.transfer_charged(self.env().caller(), to, amount)
+        }
+
+        /// Move `amount` tokens from `from` to `to`, charging the transfer fee and reflection.
+        /// Every message moving tokens between holders goes through here or `charge_transfer`.
+        fn transfer_charged(&mut self, from: AccountId, to: AccountId, amount: u128) -> Result<(), Error> {
+            self.charge_transfer(from, amount, |token, net| token.transfer_from_to(from, to, net))
+        }
+
+        /// Take `amount` tokens from `from`, checking the minimum transfer and charging
+        /// the transfer fee and reflection, and pass what is left to `deliver`.
+        /// If a fee is configured, `amount * fee_bps / 10000` (rounded down) goes to the
+        /// fee collector. If a reflection is configured, `amount * reflection_bps / 10000`
+        /// goes to the contract account and is shared among all other holders, including
+        /// `from` and the recipient, by their balances after the transfer.
+        /// `deliver` runs first and must fail without writing anything, so either every
+        /// leg is applied or none is.
+        fn charge_transfer<F>(&mut self, from: AccountId, amount: u128, deliver: F) -> Result<(), Error>
+        where
+            F: FnOnce(&mut Self, u128) -> Result<(), Error>,
+        {
+            self.ensure_above_minimum(from, amount)?;
+            let fee = bps_of(amount, self.fee_bps);
+            let mut reflection = bps_of(amount, self.reflection_bps);
+            if fee == 0 && reflection == 0 {
+                return deliver(self, amount);
+            }
+
+            // Check all legs up front so a later leg can't fail after the first one
+            if self.balance_of(from) < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            let fee_collector = self.fee_collector;
+            if fee > 0 {
+                self.ensure_not_frozen(fee_collector)?;
+                self.ensure_valid_recipient(fee_collector)?;
+                if fee_collector != from {
+                    let collector_balance = self.balance_of(fee_collector).saturating_add(fee);
+                    self.ensure_within_max_balance(fee_collector, collector_balance)?;
+                }
+            }
+            let pool = self.env().account_id();
+            let mut per_share = self.magnified_dividend_per_share;
+            if reflection > 0 {
+                match self.per_share_after(reflection)? {
+                    Some(new_per_share) => {
+                        self.ensure_not_frozen(pool)?;
+                        per_share = new_per_share;
+                    }
+                    None => reflection = 0,
+                }
+            }
+
+            deliver(self, amount - fee - reflection)?;
+            if fee > 0 {
+                self.transfer_from_to(from, fee_collector, fee)?;
+            }
+            if reflection > 0 {
+                self.transfer_from_to(from, pool, reflection)?;
+                // Balances are final, so the new reflections accrue to them from here on
+                self.magnified_dividend_per_share = per_share;
+            }
+            Ok(())
+        }
+
+        /// Returns `magnified_dividend_per_share` after sharing `amount` tokens moved into
+        /// the contract account among all other holders by their resulting balances,
+        /// or `None` if no tokens would be left outside the contract account.
+        fn per_share_after(&self, amount: u128) -> Result<Option<u128>, Error> {
+            let holder_supply = self.total_supply
+                .saturating_sub(self.balance_of(self.env().account_id()))
+                .saturating_sub(amount);
+            if holder_supply == 0 {
+                return Ok(None);
+            }
+            magnify(amount, holder_supply)
+                .and_then(|delta| self.magnified_dividend_per_share.checked_add(delta))
+                .map(Some)
+                .ok_or(Error::Overflow)
+        }
+
+        /// Move `amount` tokens from `from` to `to` using the caller's allowance.
+        fn transfer_with_allowance(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            amount: u128,
+        ) -> Result<(), Error> {
+            self.ensure_not_paused(Operation::Transfer)?;
+            self.non_reentrant(|token| {
+                let caller = token.env().caller();
+                let allowance = token.allowance(from, caller);
+
+                if allowance < amount {
+                    return Err(Error::InsufficientAllowance);
+                }
+
+                // Moves the balance or fails without writing anything
+                token.transfer_charged(from, to, amount)?;
+                token.spend_allowance(from, caller, allowance, amount);
+
+                Ok(())
+            })
+        }
+
+        /// Lower `spender`'s checked `allowance` over `owner`'s tokens by `amount`.
+        /// An `UNLIMITED_ALLOWANCE` is left as is, saving the storage write.
+        fn spend_allowance(&mut self, owner: AccountId, spender: AccountId, allowance: u128, amount: u128) {
+            if allowance == UNLIMITED_ALLOWANCE {
+                return;
+            }
+            let new_allowance = allowance.checked_sub(amount)
+                .expect("Allowance underflow");
+            self.allowances.insert((owner, spender), &new_allowance);
+        }
+
+        /// Store the allowance of `spender` over `owner`'s tokens and emit the event.
+        fn set_allowance(&mut self, owner: AccountId, spender: AccountId, amount: u128) {
+            self.allowances.insert((owner, spender), &amount);
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                value: amount,
+            });
+        }
+
+        /// Move `amount` tokens from `from` to `to`.
+        /// All checks happen before any storage is written.
+        fn transfer_from_to(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            amount: u128,
+        ) -> Result<(), Error> {
+            self.ensure_not_frozen(from)?;
+            self.ensure_not_frozen(to)?;
+            self.ensure_valid_recipient(to)?;
+            let from_balance = self.balances.get(from).unwrap_or(0);
+
+            // A self-transfer credits the already debited balance
+            let new_from_balance = from_balance.checked_sub(amount)
+                .ok_or(Error::InsufficientBalance)?;
+            let to_balance = if from == to {
+                new_from_balance
+            } else {
+                self.balances.get(to).unwrap_or(0)
+            };
+            let new_to_balance = to_balance.checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            self.ensure_within_max_balance(to, new_to_balance)?;
+
+            self.set_balance(from, new_from_balance);
+            self.set_balance(to, new_to_balance);
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value: amount,
+            });
+            
+            Ok(())
+        }
+
+        /// Move `amount` tokens from `from` into a lock for `to`.
+        /// All checks happen before any storage is written.
+        fn lock_tokens(&mut self, from: AccountId, to: AccountId, amount: u128, unlock_at: Timestamp) -> Result<(), Error> {
+            self.ensure_not_frozen(from)?;
+            self.ensure_not_frozen(to)?;
+            self.ensure_valid_recipient(to)?;
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if unlock_at > self.env().block_timestamp().saturating_add(MAX_LOCK_DURATION) {
+                return Err(Error::LockTooLong);
+            }
+            let new_from_balance = self.balance_of(from).checked_sub(amount)
+                .ok_or(Error::InsufficientBalance)?;
+            let to_balance = if to == from {
+                new_from_balance
+            } else {
+                self.balance_of(to)
+            };
+            self.ensure_within_max_balance(to, to_balance.saturating_add(amount))?;
+            let mut locks = self.locks.get(to).unwrap_or_default();
+            if locks.len() >= MAX_LOCKS {
+                return Err(Error::TooManyLocks);
+            }
+
+            self.set_balance(from, new_from_balance);
+            locks.push(Lock { from, amount, unlock_at });
+            self.locks.insert(to, &locks);
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value: amount,
+            });
+            Ok(())
+        }
+    }
+
+    impl PSP22 for Token {
+        /// Get the total amount of tokens in existence.
+        /// Minting and burning change the supply; transfers just move tokens around.
+        #[ink(message)]
+        fn total_supply(&self) -> u128 {
+            self.total_supply
+        }
+
+        /// Get the balance of a specific account.
+        /// Returns 0 if the account has no balance.
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u128 {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        /// Get the amount `spender` is still allowed to spend on behalf of `owner`.
+        /// Returns 0 if no allowance has been set.
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> u128 {
+            if let Some(expiry) = self.allowance_expiry.get((owner, spender)) {
+                if self.env().block_timestamp() >= expiry {
+                    return 0;
+                }
+            }
+            self.allowances.get((owner, spender)).unwrap_or(0)
+        }
+
+        /// Transfer tokens from the caller to another account.
+        /// If a fee is configured, `amount * fee_bps / 10000` (rounded down) goes to the
+        /// fee collector and the remainder to `to`. `data` is ignored.
+        /// Returns an error if the contract is paused or the caller has insufficient balance.
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: u128, _data: Vec<u8>) -> Result<(), PSP22Error> {
+            self.transfer_from_caller(to, value)?;
+            Ok(())
+        }
+
+        /// Transfer tokens on behalf of `from` using the caller's allowance.
+        /// The allowance goes down by `value` unless it is `UNLIMITED_ALLOWANCE`.
+        /// `data` is ignored.
+        /// Returns an error if the contract is paused or the allowance or `from`'s
+        /// balance is insufficient.
+        #[ink(message)]
+        fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: u128,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            self.transfer_with_allowance(from, to, value)?;
+            Ok(())
+        }
+
+        /// Allow `spender` to spend up to `value` of the caller's tokens.
+        /// This overwrites any existing allowance rather than adding to it.
+        /// Approving `UNLIMITED_ALLOWANCE` lets `spender` spend without it ever decreasing.
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: u128) -> Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            self.set_allowance(owner, spender, value);
+            self.allowance_expiry.remove((owner, spender));
+            Ok(())
+        }
+
+        /// Increase the allowance of `spender` over the caller's tokens by `delta_value`.
+        /// An expired allowance counts as zero and loses its expiry, so the increase is usable.
+        #[ink(message)]
+        fn increase_allowance(
+            &mut self,
+            spender: AccountId,
+            delta_value: u128,
+        ) -> Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            let new_allowance = self.allowance(owner, spender).checked_add(delta_value)
+                .ok_or(Error::Overflow)?;
+            let expired = self.allowance_expiry.get((owner, spender))
+                .is_some_and(|expiry| self.env().block_timestamp() >= expiry);
+            if expired {
+                self.allowance_expiry.remove((owner, spender));
+            }
+            self.set_allowance(owner, spender, new_allowance);
+            Ok(())
+        }
+
+        /// Decrease the allowance of `spender` over the caller's tokens by `delta_value`.
+        /// Returns an error instead of saturating if `delta_value` exceeds the current allowance.
+        #[ink(message)]
+        fn decrease_allowance(
+            &mut self,
+            spender: AccountId,
+            delta_value: u128,
+        ) -> Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            let new_allowance = self.allowance(owner, spender).checked_sub(delta_value)
+                .ok_or(Error::InsufficientAllowance)?;
+            self.set_allowance(owner, spender, new_allowance);
+            Ok(())
+        }
+    }
+
+    impl Ownable for Token {
+        /// Get the current owner of the contract.
+        #[ink(message)]
+        fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Get the account offered ownership, if any.
+        #[ink(message)]
+        fn pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Offer ownership to `new_owner`, replacing any earlier offer.
+        /// The current owner keeps all rights until `new_owner` accepts.
+        #[ink(message)]
+        fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), OwnableError> {
+            ownable::only_owner(self.env().caller(), self.owner)?;
+            self.pending_owner = Some(new_owner);
+            Ok(())
+        }
+
+        /// Take over the offered ownership, which also exempts the caller from the maximum balance.
+        #[ink(message)]
+        fn accept_ownership(&mut self) -> Result<(), OwnableError> {
+            let caller = self.env().caller();
+            ownable::only_pending_owner(caller, self.pending_owner)?;
+            self.pending_owner = None;
+            self.set_owner(caller);
+            Ok(())
+        }
+
+        /// Withdraw the pending ownership offer.
+        #[ink(message)]
+        fn cancel_ownership_transfer(&mut self) -> Result<(), OwnableError> {
+            ownable::only_owner(self.env().caller(), self.owner)?;
+            self.pending_owner = None;
+            Ok(())
+        }
+
+        /// Give up ownership by handing it to the zero account, dropping any pending offer.
+        /// Only the owner's own minting ends; accounts in the minter set keep minting,
+        /// and since nobody can remove them anymore, keep that role for good.
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> Result<(), OwnableError> {
+            ownable::only_owner(self.env().caller(), self.owner)?;
+            self.pending_owner = None;
+            self.set_owner(ownable::RENOUNCED);
+            Ok(())
+        }
+    }
+
+    /// `amount * bps / 10000` rounded down, split so large amounts can't overflow.
+    fn bps_of(amount: u128, bps: u16) -> u128 {
+        let bps = u128::from(bps);
+        amount / 10_000 * bps + amount % 10_000 * bps / 10_000
+    }
+
+    /// `amount * 2^64 / supply` rounded down, or `None` if it overflows or `supply` is zero.
+    fn magnify(amount: u128, supply: u128) -> Option<u128> {
+        if supply == 0 {
+            return None;
+        }
+        let whole = (amount / supply).checked_mul(1 << 64)?;
+        // Long division of the remainder one bit at a time, since shifting it
+        // left by 64 bits at once could overflow
+        let mut remainder = amount % supply;
+        let mut fraction = 0u128;
+        for _ in 0..64 {
+            let carry = remainder >> 127;
+            remainder <<= 1;
+            fraction <<= 1;
+            if carry == 1 || remainder >= supply {
+                remainder = remainder.wrapping_sub(supply);
+                fraction |= 1;
+            }
+        }
+        whole.checked_add(fraction)
+    }
+
+    /// `balance * per_share / 2^64` rounded down, saturating at `u128::MAX`.
+    fn demagnify(balance: u128, per_share: u128) -> u128 {
+        // Multiply 64-bit halves so the intermediate 256-bit product never overflows
+        let low_mask = u128::from(u64::MAX);
+        let (balance_high, balance_low) = (balance >> 64, balance & low_mask);
+        let (share_high, share_low) = (per_share >> 64, per_share & low_mask);
+        (balance_high * share_high).checked_mul(1 << 64)
+            .and_then(|sum| sum.checked_add(balance_high * share_low))
+            .and_then(|sum| sum.checked_add(balance_low * share_high))
+            .and_then(|sum| sum.checked_add((balance_low * share_low) >> 64))
+            .unwrap_or(u128::MAX)
+    }
+
+    /// Custom error types for the token contract.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        InsufficientBalance,
+        InsufficientAllowance,
+        NotOwner,
+        CapExceeded,
+        Paused,
+        LengthMismatch,
+        Overflow,
+        AccountFrozen,
+        Reentrancy,
+        InvalidFee,
+        VestingExists,
+        NoVestingSchedule,
+        AlreadyClaimed,
+        InvalidProof,
+        ZeroAddress,
+        TransferRejected,
+        MaxBalanceExceeded,
+        BlockNotYetMined,
+        UpgradeFailed,
+        AlreadyMigrated,
+        FaucetDisabled,
+        CooldownActive,
+        NonceUsed,
+        InvalidSignature,
+        CannotRescueOwnToken,
+        RescueFailed,
+        NoHolders,
+        Expired,
+        NativeTransferFailed,
+        CallbackFailed,
+        BelowMinimum,
+        SwapNotFound,
+        SwapExpired,
+        SwapFailed,
+        MinterCapExceeded,
+        NotPaused,
+        TooManyLocks,
+        ZeroAmount,
+        LockTooLong,
+    }
+
+    impl From<OwnableError> for Error {
+        fn from(error: OwnableError) -> Self {
+            match error {
+                OwnableError::NotOwner | OwnableError::NotPendingOwner => Error::NotOwner,
+            }
+        }
+    }
+
+    impl From<Error> for PSP22Error {
+        fn from(error: Error) -> Self {
+            match error {
+                Error::InsufficientBalance => PSP22Error::InsufficientBalance,
+                Error::InsufficientAllowance => PSP22Error::InsufficientAllowance,
+                Error::ZeroAddress => PSP22Error::ZeroRecipientAddress,
+                other => PSP22Error::Custom(format!("{:?}", other)),
+            }
+        }
+    }
+
+    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
+    /// module and test functions are marked with a `#[test]` attribute.
+    /// The below code is technically just normal Rust code.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+
+        /// We test if the default constructor does its job.
+        #[ink::test]
+        fn default_works() {
+            let token = Token::default();
+            // Test that a new account has zero balance
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.balance_of(accounts.alice), 0);
+        }
+
+        /// We test that the default metadata is empty with 18 decimals.
+        #[ink::test]
+        fn default_metadata_works() {
+            let token = Token::new();
+            assert_eq!(token.token_name(), "");
+            assert_eq!(token.token_symbol(), "");
+            assert_eq!(token.token_decimals(), 18);
+        }
+
+        /// We test the metadata constructor.
+        #[ink::test]
+        fn new_with_metadata_works() {
+            let token = Token::new_with_metadata("Polkadot Token".into(), "PDT".into(), 10);
+            assert_eq!(token.token_name(), "Polkadot Token");
+            assert_eq!(token.token_symbol(), "PDT");
+            assert_eq!(token.token_decimals(), 10);
+        }
+
+        /// We test minting functionality.
+        #[ink::test]
+        fn mint_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            
+            // Mint 100 tokens to Alice
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            
+            // Mint more tokens to Alice
+            assert!(token.mint(accounts.alice, 50).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 150);
+        }
+
+        /// We test that minting past u128::MAX returns an error instead of panicking.
+        #[ink::test]
+        fn mint_overflow() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            assert!(token.mint(accounts.alice, u128::MAX).is_ok());
+            assert_eq!(token.mint(accounts.alice, 1), Err(Error::Overflow));
+            assert_eq!(token.balance_of(accounts.alice), u128::MAX);
+            assert_eq!(token.total_supply(), u128::MAX);
+        }
+
+        /// We test transfer functionality.
+        #[ink::test]
+        fn transfer_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            
+            // Mint tokens to Alice
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.balance_of(accounts.bob), 0);
+            
+            // Set Alice as the caller for the transfer
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            
+            // Transfer 30 tokens from Alice to Bob
+            let result = token.transfer(accounts.bob, 30, Vec::new());
+            assert!(result.is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 70);
+            assert_eq!(token.balance_of(accounts.bob), 30);
+        }
+
+        /// A fully built cross-contract message call.
+        type MessageCall<Args, R> = ink::env::call::CallBuilder<
+            ink::env::DefaultEnvironment,
+            ink::env::call::utils::Set<ink::env::call::Call<ink::env::DefaultEnvironment>>,
+            ink::env::call::utils::Set<ink::env::call::ExecutionInput<Args>>,
+            ink::env::call::utils::Set<ink::env::call::utils::ReturnType<R>>,
+        >;
+
+        /// Returns the selector a call builder message encodes for.
+        fn selector_of<Args: scale::Encode, R>(call: MessageCall<Args, R>) -> [u8; 4] {
+            let encoded = scale::Encode::encode(call.params().exec_input());
+            [encoded[0], encoded[1], encoded[2], encoded[3]]
+        }
+
+        /// We test that the PSP22 messages resolve to the canonical selectors.
+        #[ink::test]
+        fn psp22_selectors_work() {
+            use ink::codegen::TraitCallBuilder;
+
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut token: TokenRef = ink::env::call::FromAccountId::from_account_id(accounts.alice);
+            let builder = token.call_mut();
+
+            assert_eq!(selector_of(builder.total_supply()), [0x16, 0x2d, 0xf8, 0xc2]);
+            assert_eq!(selector_of(builder.balance_of(accounts.bob)), [0x65, 0x68, 0x38, 0x2f]);
+            assert_eq!(selector_of(builder.allowance(accounts.bob, accounts.charlie)), [0x4d, 0x47, 0xd9, 0x21]);
+            assert_eq!(selector_of(builder.transfer(accounts.bob, 1, Vec::new())), [0xdb, 0x20, 0xf9, 0xf5]);
+            assert_eq!(
+                selector_of(builder.transfer_from(accounts.bob, accounts.charlie, 1, Vec::new())),
+                [0x54, 0xb3, 0xc7, 0x6e]
+            );
+            assert_eq!(selector_of(builder.approve(accounts.bob, 1)), [0xb2, 0x0f, 0x1b, 0xbd]);
+            assert_eq!(selector_of(builder.increase_allowance(accounts.bob, 1)), [0x96, 0xd6, 0xb5, 0x7a]);
+            assert_eq!(selector_of(builder.decrease_allowance(accounts.bob, 1)), [0xfe, 0xcb, 0x57, 0xd5]);
+        }
+
+        /// We test that minting keeps track of the total supply.
+        #[ink::test]
+        fn total_supply_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.total_supply(), 0);
+
+            // Mint tokens to Alice and Bob
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.mint(accounts.bob, 50).is_ok());
+            assert_eq!(
+                token.total_supply(),
+                token.balance_of(accounts.alice) + token.balance_of(accounts.bob)
+            );
+            assert_eq!(token.total_supply(), 150);
+
+            // Transfers move tokens without changing the supply
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.transfer(accounts.bob, 30, Vec::new()).is_ok());
+            assert_eq!(token.total_supply(), 150);
+        }
+
+        /// We test transfer with insufficient balance.
+        #[ink::test]
+        fn transfer_insufficient_balance() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            
+            // Set Alice as the caller (she has no balance)
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            
+            // Try to transfer more than Alice has
+            let result = token.transfer(accounts.bob, 100, Vec::new());
+            assert!(result.is_err());
+            assert_eq!(result.unwrap_err(), PSP22Error::InsufficientBalance);
+        }
+
+        /// We test burning the entire balance.
+        #[ink::test]
+        fn burn_full_balance_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            // Burn everything Alice owns
+            assert!(token.burn(100).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 0);
+            assert_eq!(token.total_supply(), 0);
+            // The zero balance entry is removed from storage
+            assert!(!token.balances.contains(accounts.alice));
+        }
+
+        /// We test burning part of the balance.
+        #[ink::test]
+        fn burn_partial_balance_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            // Burn 40 of Alice's 100 tokens
+            assert!(token.burn(40).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 60);
+            assert_eq!(token.total_supply(), 60);
+        }
+
+        /// We test burning more than the caller owns.
+        #[ink::test]
+        fn burn_insufficient_balance() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            // Try to burn more than Alice has
+            let result = token.burn(101);
+            assert_eq!(result, Err(Error::InsufficientBalance));
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.total_supply(), 100);
+        }
+
+        /// We test approving and reading back an allowance.
+        #[ink::test]
+        fn approve_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+
+            // Alice allows Bob to spend 50 tokens
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 50).is_ok());
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 50);
+            assert_eq!(token.allowance(accounts.bob, accounts.alice), 0);
+        }
+
+        /// We test that an allowance stops working once it expires.
+        #[ink::test]
+        fn allowance_expiry_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            token.approve_with_expiry(accounts.bob, 50, 2000);
+            assert_eq!(token.allowance_expiry(accounts.alice, accounts.bob), Some(2000));
+
+            // Valid before the expiry
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer_from(accounts.alice, accounts.charlie, 10, Vec::new()).is_ok());
+            assert!(token.burn_from(accounts.alice, 10).is_ok());
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 30);
+
+            // Treated as zero from the expiry on
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2000);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 10, Vec::new()),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            assert_eq!(token.burn_from(accounts.alice, 10), Err(Error::InsufficientAllowance));
+
+            // Increasing an expired allowance starts from zero without the old expiry
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.increase_allowance(accounts.bob, 15).is_ok());
+            assert_eq!(token.allowance_expiry(accounts.alice, accounts.bob), None);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 15);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer_from(accounts.alice, accounts.charlie, 15, Vec::new()).is_ok());
+
+            // A plain approve never expires
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 20).is_ok());
+            assert_eq!(token.allowance_expiry(accounts.alice, accounts.bob), None);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 20);
+        }
+
+        /// We test that approve overwrites an existing allowance.
+        #[ink::test]
+        fn approve_overwrites_allowance() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            assert!(token.approve(accounts.bob, 50).is_ok());
+            assert!(token.approve(accounts.bob, 20).is_ok());
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 20);
+        }
+
+        /// We test spending an allowance with transfer_from.
+        #[ink::test]
+        fn transfer_from_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            // Alice allows Bob to spend 50 tokens
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 50).is_ok());
+
+            // Bob moves 30 of Alice's tokens to Charlie
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = token.transfer_from(accounts.alice, accounts.charlie, 30, Vec::new());
+            assert!(result.is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 70);
+            assert_eq!(token.balance_of(accounts.charlie), 30);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 20);
+        }
+
+        /// We test transfer_from with insufficient allowance.
+        #[ink::test]
+        fn transfer_from_insufficient_allowance() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 10).is_ok());
+
+            // Bob tries to spend more than he was allowed
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = token.transfer_from(accounts.alice, accounts.charlie, 30, Vec::new());
+            assert_eq!(result, Err(PSP22Error::InsufficientAllowance));
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 10);
+        }
+
+        /// We test transfer_from with a high allowance but insufficient balance.
+        #[ink::test]
+        fn transfer_from_insufficient_balance() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 20).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 1000).is_ok());
+
+            // Bob is allowed enough but Alice doesn't own enough
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = token.transfer_from(accounts.alice, accounts.charlie, 30, Vec::new());
+            assert_eq!(result, Err(PSP22Error::InsufficientBalance));
+            // Neither the balances nor the allowance changed
+            assert_eq!(token.balance_of(accounts.alice), 20);
+            assert_eq!(token.balance_of(accounts.charlie), 0);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 1000);
+        }
+
+        /// We test increasing an allowance from zero and from an existing value.
+        #[ink::test]
+        fn increase_allowance_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            // Increase from zero
+            assert!(token.increase_allowance(accounts.bob, 30).is_ok());
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 30);
+
+            // Increase an existing allowance
+            assert!(token.increase_allowance(accounts.bob, 20).is_ok());
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 50);
+        }
+
+        /// We test decreasing an allowance, including below zero.
+        #[ink::test]
+        fn decrease_allowance_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 50).is_ok());
+
+            assert!(token.decrease_allowance(accounts.bob, 20).is_ok());
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 30);
+
+            // Decreasing below zero fails and leaves the allowance untouched
+            let result = token.decrease_allowance(accounts.bob, 31);
+            assert_eq!(result, Err(PSP22Error::InsufficientAllowance));
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 30);
+        }
+
+        /// We test that mint and transfer emit Transfer events.
+        #[ink::test]
+        fn transfer_emits_event() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.transfer(accounts.bob, 30, Vec::new()).is_ok());
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+
+            // The mint is reported as a transfer from nobody
+            let minted = <Transfer as scale::Decode>::decode(&mut &events[0].data[..])
+                .expect("decoding failed");
+            assert_eq!(minted.from, None);
+            assert_eq!(minted.to, Some(accounts.alice));
+            assert_eq!(minted.value, 100);
+
+            let transferred = <Transfer as scale::Decode>::decode(&mut &events[1].data[..])
+                .expect("decoding failed");
+            assert_eq!(transferred.from, Some(accounts.alice));
+            assert_eq!(transferred.to, Some(accounts.bob));
+            assert_eq!(transferred.value, 30);
+        }
+
+        /// We test that allowance changes emit Approval events with the new value.
+        #[ink::test]
+        fn approve_emits_event() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 50).is_ok());
+            assert!(token.increase_allowance(accounts.bob, 10).is_ok());
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+
+            let approval = <Approval as scale::Decode>::decode(&mut &events[0].data[..])
+                .expect("decoding failed");
+            assert_eq!(approval.owner, accounts.alice);
+            assert_eq!(approval.spender, accounts.bob);
+            assert_eq!(approval.value, 50);
+
+            // The increase reports the absolute allowance, not the delta
+            let increased = <Approval as scale::Decode>::decode(&mut &events[1].data[..])
+                .expect("decoding failed");
+            assert_eq!(increased.value, 60);
+        }
+
+        /// We test that only the owner can mint.
+        #[ink::test]
+        fn mint_not_owner() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.owner(), accounts.alice);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.mint(accounts.bob, 100), Err(Error::NotOwner));
+            assert_eq!(token.balance_of(accounts.bob), 0);
+        }
+
+        /// We test handing ownership to another account.
+        #[ink::test]
+        fn transfer_ownership_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // Bob is not the owner and can't take over
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.transfer_ownership(accounts.bob), Err(OwnableError::NotOwner));
+
+            // Alice offers ownership to Bob, but stays owner until he accepts
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.transfer_ownership(accounts.bob).is_ok());
+            assert_eq!(token.owner(), accounts.alice);
+            assert_eq!(token.pending_owner(), Some(accounts.bob));
+
+            // Once Bob accepts he can mint and Alice can't
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.accept_ownership().is_ok());
+            assert_eq!(token.owner(), accounts.bob);
+            assert_eq!(token.pending_owner(), None);
+            assert!(token.mint(accounts.bob, 100).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token.mint(accounts.alice, 100), Err(Error::NotOwner));
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let transferred = <OwnershipTransferred as scale::Decode>::decode(&mut &events[0].data[..])
+                .expect("decoding failed");
+            assert_eq!(transferred.previous_owner, accounts.alice);
+            assert_eq!(transferred.new_owner, accounts.bob);
+        }
+
+        /// We test that only the pending owner can accept ownership.
+        #[ink::test]
+        fn accept_ownership_requires_pending_owner() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // Nothing to accept before an offer
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.accept_ownership(), Err(OwnableError::NotPendingOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.transfer_ownership(accounts.bob).is_ok());
+
+            // Charlie isn't the one offered ownership
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(token.accept_ownership(), Err(OwnableError::NotPendingOwner));
+            assert_eq!(token.owner(), accounts.alice);
+            assert_eq!(token.pending_owner(), Some(accounts.bob));
+        }
+
+        /// We test that the owner can withdraw an ownership offer.
+        #[ink::test]
+        fn cancel_ownership_transfer_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.transfer_ownership(accounts.bob).is_ok());
+
+            // Only the owner can cancel
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.cancel_ownership_transfer(), Err(OwnableError::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.cancel_ownership_transfer().is_ok());
+            assert_eq!(token.pending_owner(), None);
+
+            // Bob can no longer accept
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.accept_ownership(), Err(OwnableError::NotPendingOwner));
+            assert_eq!(token.owner(), accounts.alice);
+        }
+
+        /// We test that balances are looked up in order and the query is capped.
+        #[ink::test]
+        fn balances_of_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.bob, 100).is_ok());
+            assert!(token.mint(accounts.django, 5).is_ok());
+
+            assert_eq!(
+                token.balances_of(vec![accounts.bob, accounts.charlie, accounts.django, accounts.bob]),
+                vec![100, 0, 5, 100]
+            );
+            assert_eq!(token.balances_of(Vec::new()), Vec::<u128>::new());
+            assert_eq!(
+                token.balances_of(vec![accounts.bob; MAX_BALANCE_QUERIES + 1]),
+                vec![100; MAX_BALANCE_QUERIES]
+            );
+        }
+
+        /// We test that a swap within this token moves both sides at once.
+        #[ink::test]
+        fn accept_swap_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let this = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.bob, 100).is_ok());
+            assert!(token.mint(accounts.charlie, 100).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.create_swap(accounts.charlie, 30, this, 50), Ok(0));
+            assert_eq!(token.balance_of(accounts.bob), 70);
+            assert_eq!(token.balance_of(this), 30);
+            assert_eq!(token.swap(0).map(|swap| swap.give), Some(30));
+
+            // Only the counterparty can accept
+            assert_eq!(token.accept_swap(0), Err(Error::SwapNotFound));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(token.accept_swap(0), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 120);
+            assert_eq!(token.balance_of(accounts.charlie), 80);
+            assert_eq!(token.balance_of(this), 0);
+            assert_eq!(token.swap(0), None);
+            assert_eq!(token.accept_swap(0), Err(Error::SwapNotFound));
+
+            // The counterparty must be able to pay
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.create_swap(accounts.charlie, 10, this, 500), Ok(1));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(token.accept_swap(1), Err(Error::InsufficientBalance));
+            assert_eq!(token.balance_of(this), 10);
+        }
+
+        /// We test that cancelling returns the escrow and expired swaps can't be accepted.
+        #[ink::test]
+        fn cancel_swap_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let this = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.bob, 100).is_ok());
+            assert!(token.mint(accounts.charlie, 100).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.create_swap(accounts.charlie, 30, this, 50), Ok(0));
+            assert_eq!(token.create_swap(accounts.charlie, 200, this, 50), Err(Error::InsufficientBalance));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(SWAP_DURATION);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(token.accept_swap(0), Err(Error::SwapExpired));
+            assert_eq!(token.cancel_swap(0), Err(Error::SwapNotFound));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.cancel_swap(0), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(token.balance_of(accounts.charlie), 100);
+            assert_eq!(token.balance_of(this), 0);
+            assert_eq!(token.cancel_swap(0), Err(Error::SwapNotFound));
+        }
+
+        /// We test that supply changes move the owner's balance and emit the new total.
+        #[ink::test]
+        fn supply_changes_work() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.expand_supply(100), Ok(()));
+            assert_eq!(token.total_supply(), 100);
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.contract_supply(30), Ok(()));
+            assert_eq!(token.total_supply(), 70);
+            assert_eq!(token.balance_of(accounts.alice), 70);
+            assert_eq!(token.contract_supply(71), Err(Error::InsufficientBalance));
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 4);
+            let expanded = <SupplyChanged as scale::Decode>::decode(&mut &events[1].data[..])
+                .expect("decoding failed");
+            assert_eq!(expanded.new_total, 100);
+            let contracted = <SupplyChanged as scale::Decode>::decode(&mut &events[3].data[..])
+                .expect("decoding failed");
+            assert_eq!(contracted.new_total, 70);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.expand_supply(100), Err(Error::NotOwner));
+            assert_eq!(token.contract_supply(10), Err(Error::NotOwner));
+            assert_eq!(token.total_supply(), 70);
+        }
+
+        /// We test that renouncing ends the owner's minting but not the minters'.
+        #[ink::test]
+        fn renounce_ownership_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.add_minter(accounts.bob).is_ok());
+
+            assert!(token.renounce_ownership().is_ok());
+            assert_eq!(token.owner(), AccountId::from([0u8; 32]));
+            assert_eq!(token.mint(accounts.alice, 100), Err(Error::NotOwner));
+            assert_eq!(token.renounce_ownership(), Err(OwnableError::NotOwner));
+            assert_eq!(ink::env::test::recorded_events().count(), 1);
+
+            // Minters stay, and nobody can remove them anymore
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.mint(accounts.bob, 100).is_ok());
+            assert_eq!(token.remove_minter(accounts.bob), Err(Error::NotOwner));
+        }
+
+        /// We test adding and removing a minter.
+        #[ink::test]
+        fn minters_work() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(!token.is_minter(accounts.bob));
+
+            // Alice makes Bob a minter
+            assert!(token.add_minter(accounts.bob).is_ok());
+            assert!(token.is_minter(accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.mint(accounts.charlie, 100).is_ok());
+            assert_eq!(token.balance_of(accounts.charlie), 100);
+
+            // Only the owner manages the minter set
+            assert_eq!(token.add_minter(accounts.charlie), Err(Error::NotOwner));
+
+            // Alice removes Bob, who can no longer mint
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.remove_minter(accounts.bob).is_ok());
+            assert!(!token.is_minter(accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.mint(accounts.charlie, 100), Err(Error::NotOwner));
+            assert_eq!(token.balance_of(accounts.charlie), 100);
+        }
+
+        /// We test that a capped minter spends its budget and the owner can refill it.
+        #[ink::test]
+        fn minter_budget_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.add_minter(accounts.bob).is_ok());
+            assert_eq!(token.minter_budget(accounts.bob), None);
+            assert_eq!(token.set_minter_budget(accounts.bob, Some(100)), Ok(()));
+            assert_eq!(token.minter_budget(accounts.bob), Some(100));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.set_minter_budget(accounts.bob, None), Err(Error::NotOwner));
+            assert_eq!(token.top_up_minter_budget(accounts.bob, 50), Err(Error::NotOwner));
+            assert!(token.mint(accounts.charlie, 60).is_ok());
+            assert_eq!(token.minter_budget(accounts.bob), Some(40));
+            assert_eq!(token.mint(accounts.charlie, 41), Err(Error::MinterCapExceeded));
+            assert!(token.mint(accounts.charlie, 40).is_ok());
+            assert_eq!(token.mint(accounts.charlie, 1), Err(Error::MinterCapExceeded));
+            assert_eq!(token.balance_of(accounts.charlie), 100);
+
+            // The owner refills the budget and is never capped itself
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token.top_up_minter_budget(accounts.bob, 25), Ok(()));
+            assert!(token.mint(accounts.alice, 1000).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.mint(accounts.charlie, 25).is_ok());
+            assert_eq!(token.minter_budget(accounts.bob), Some(0));
+
+            // Lifting the cap makes the minter unlimited again
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token.set_minter_budget(accounts.bob, None), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.mint(accounts.charlie, 500).is_ok());
+        }
+
+        /// We test minting exactly up to the cap and just over it.
+        #[ink::test]
+        fn mint_up_to_cap_works() {
+            let mut token = Token::new_with_cap(100);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.cap(), 100);
+
+            assert!(token.mint(accounts.alice, 60).is_ok());
+            assert!(token.mint(accounts.bob, 40).is_ok());
+            assert_eq!(token.total_supply(), 100);
+
+            // Any further mint goes over the cap
+            assert_eq!(token.mint(accounts.bob, 1), Err(Error::CapExceeded));
+            assert_eq!(token.balance_of(accounts.bob), 40);
+            assert_eq!(token.total_supply(), 100);
+        }
+
+        /// We test that a zero cap forbids minting entirely.
+        #[ink::test]
+        fn zero_cap_forbids_mint() {
+            let mut token = Token::new_with_cap(0);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.mint(accounts.alice, 1), Err(Error::CapExceeded));
+            assert_eq!(token.total_supply(), 0);
+        }
+
+        /// We test that dust transfers are rejected unless they close the account.
+        #[ink::test]
+        fn min_transfer_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.bob, 100).is_ok());
+            assert_eq!(token.set_min_transfer(10), Ok(()));
+            assert_eq!(token.min_transfer(), 10);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.set_min_transfer(0), Err(Error::NotOwner));
+            assert_eq!(token.transfer(accounts.charlie, 9, Vec::new()), Err(Error::BelowMinimum.into()));
+            assert!(token.transfer(accounts.charlie, 10, Vec::new()).is_ok());
+            assert!(token.approve(accounts.django, 50).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(
+                token.transfer_from(accounts.bob, accounts.django, 5, Vec::new()),
+                Err(Error::BelowMinimum.into())
+            );
+            assert!(token.transfer_from(accounts.bob, accounts.django, 10, Vec::new()).is_ok());
+
+            // The owner is exempt
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.transfer(accounts.eve, 5, Vec::new()).is_ok());
+
+            // Emptying a balance is allowed below the minimum
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(token.transfer(accounts.django, 4, Vec::new()), Err(Error::BelowMinimum.into()));
+            assert!(token.transfer(accounts.django, 5, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.eve), 0);
+        }
+
+        /// We test that batches and locks can't get around the minimum transfer.
+        #[ink::test]
+        fn min_transfer_applies_to_batches_and_locks() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.bob, 100).is_ok());
+            assert_eq!(token.set_min_transfer(10), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.batch_transfer(vec![accounts.charlie], vec![5]), Err(Error::BelowMinimum));
+            assert_eq!(token.transfer_locked(accounts.charlie, 5, 1000), Err(Error::BelowMinimum));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(token.locked_balance_of(accounts.charlie), 0);
+
+            assert!(token.batch_transfer(vec![accounts.charlie], vec![10]).is_ok());
+            assert!(token.transfer_locked(accounts.charlie, 10, 1000).is_ok());
+            assert_eq!(token.balance_of(accounts.charlie), 10);
+            assert_eq!(token.locked_balance_of(accounts.charlie), 10);
+        }
+
+        /// We test that pausing only mint leaves transfers working.
+        #[ink::test]
+        fn operation_pause_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            assert_eq!(token.set_operation_paused(Operation::Mint, true), Ok(()));
+            assert!(token.is_operation_paused(Operation::Mint));
+            assert!(!token.is_operation_paused(Operation::Transfer));
+            assert!(!token.is_paused());
+            assert_eq!(token.mint(accounts.alice, 10), Err(Error::Paused));
+            assert_eq!(token.batch_mint(vec![accounts.bob], vec![10]), Err(Error::Paused));
+            assert!(token.transfer(accounts.bob, 10, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 10);
+            assert_eq!(token.total_supply(), 100);
+
+            // Only the owner may flip the flags
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.set_operation_paused(Operation::Mint, false), Err(Error::NotOwner));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            assert_eq!(token.set_operation_paused(Operation::Mint, false), Ok(()));
+            assert!(token.mint(accounts.alice, 10).is_ok());
+
+            // The blanket pause still covers every operation
+            assert!(token.pause().is_ok());
+            assert!(token.is_operation_paused(Operation::Transfer));
+            assert!(token.is_operation_paused(Operation::Mint));
+        }
+
+        /// We test that transfers fail while paused and resume after unpause.
+        #[ink::test]
+        fn pause_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.approve(accounts.bob, 50).is_ok());
+
+            assert!(token.pause().is_ok());
+            assert!(token.is_paused());
+            assert_eq!(token.transfer(accounts.bob, 10, Vec::new()), Err(Error::Paused.into()));
+            assert_eq!(token.mint(accounts.alice, 10), Err(Error::Paused));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.bob, 10, Vec::new()),
+                Err(Error::Paused.into())
+            );
+
+            // Only the owner can unpause
+            assert_eq!(token.unpause(), Err(Error::NotOwner));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.unpause().is_ok());
+            assert!(!token.is_paused());
+            assert!(token.transfer(accounts.bob, 10, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 10);
+        }
+
+        /// We test burning on behalf of another account.
+        #[ink::test]
+        fn burn_from_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.approve(accounts.bob, 50).is_ok());
+
+            // Bob burns 30 of Alice's tokens
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.burn_from(accounts.alice, 30).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 70);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 20);
+            assert_eq!(token.total_supply(), 70);
+        }
+
+        /// We test that an unlimited allowance survives spends while a finite one goes down.
+        #[ink::test]
+        fn unlimited_allowance_is_not_decremented() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.approve(accounts.bob, UNLIMITED_ALLOWANCE).is_ok());
+            assert!(token.approve(accounts.charlie, 50).is_ok());
+
+            // Bob spends several times and keeps the maximum
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer_from(accounts.alice, accounts.eve, 10, Vec::new()).is_ok());
+            assert!(token.transfer_from(accounts.alice, accounts.eve, 20, Vec::new()).is_ok());
+            assert!(token.burn_from(accounts.alice, 5).is_ok());
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), UNLIMITED_ALLOWANCE);
+
+            // Charlie's allowance shrinks with every spend
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(token.transfer_from(accounts.alice, accounts.eve, 10, Vec::new()).is_ok());
+            assert!(token.burn_from(accounts.alice, 5).is_ok());
+            assert_eq!(token.allowance(accounts.alice, accounts.charlie), 35);
+
+            assert_eq!(token.balance_of(accounts.alice), 50);
+            assert_eq!(token.balance_of(accounts.eve), 40);
+        }
+
+        /// We test that approve_max grants an unlimited allowance and revoke clears it.
+        #[ink::test]
+        fn approve_max_and_revoke_work() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            token.approve_with_expiry(accounts.bob, 10, 1000);
+
+            token.approve_max(accounts.bob);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), u128::MAX);
+            assert_eq!(token.allowance_expiry(accounts.alice, accounts.bob), None);
+
+            token.revoke(accounts.bob);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let granted = <Approval as scale::Decode>::decode(&mut &events[1].data[..])
+                .expect("decoding failed");
+            assert_eq!(granted.value, u128::MAX);
+            let revoked = <Approval as scale::Decode>::decode(&mut &events[2].data[..])
+                .expect("decoding failed");
+            assert_eq!(revoked.spender, accounts.bob);
+            assert_eq!(revoked.value, 0);
+        }
+
+        /// We test that only the owner can upgrade the code.
+        #[ink::test]
+        fn set_code_not_owner() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.set_code(Hash::from([1u8; 32])), Err(Error::NotOwner));
+        }
+
+        /// We test that the upgrade fixture decodes the storage this code writes.
+        #[ink::test]
+        fn upgraded_token_layout_matches() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.bob, 100).is_ok());
+            assert!(token.transfer_ownership(accounts.charlie).is_ok());
+            let mut encoded = Vec::new();
+            ink::storage::traits::Storable::encode(&token, &mut encoded);
+            let upgraded: upgraded_token::upgraded_token::Token =
+                ink::storage::traits::decode_all(&mut &encoded[..]).expect("layout mismatch");
+            assert_eq!(upgraded.version(), STORAGE_VERSION);
+        }
+
+        /// We test that migrating bumps an outdated storage version exactly once.
+        #[ink::test]
+        fn migrate_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.version(), STORAGE_VERSION);
+            assert_eq!(token.migrate(), Err(Error::AlreadyMigrated));
+
+            // Pretend the storage was written by older code
+            token.storage_version = STORAGE_VERSION - 1;
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.migrate(), Err(Error::NotOwner));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.migrate().is_ok());
+            assert_eq!(token.version(), STORAGE_VERSION);
+            assert_eq!(token.migrate(), Err(Error::AlreadyMigrated));
+        }
+
+        /// We test that recipients can't be pushed over the maximum balance unless exempt.
+        #[ink::test]
+        fn max_balance_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.set_max_balance(50).is_ok());
+            assert_eq!(token.max_balance(), 50);
+
+            // The owner is exempt
+            assert!(token.is_max_balance_exempt(accounts.alice));
+            assert!(token.mint(accounts.alice, 200).is_ok());
+
+            assert!(token.transfer(accounts.bob, 40, Vec::new()).is_ok());
+            assert_eq!(
+                token.transfer(accounts.bob, 20, Vec::new()),
+                Err(Error::MaxBalanceExceeded.into())
+            );
+            assert_eq!(token.mint(accounts.bob, 20), Err(Error::MaxBalanceExceeded));
+            assert!(token.approve(accounts.bob, 20).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.bob, 20, Vec::new()),
+                Err(Error::MaxBalanceExceeded.into())
+            );
+            assert_eq!(token.set_max_balance_exempt(accounts.bob, true), Err(Error::NotOwner));
+            assert_eq!(token.balance_of(accounts.bob), 40);
+
+            // An exempt account can exceed the cap
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.set_max_balance_exempt(accounts.bob, true).is_ok());
+            assert!(token.transfer(accounts.bob, 20, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 60);
+        }
+
+        /// We test that the owner and fee collector lose their exemption with their role.
+        #[ink::test]
+        fn max_balance_exemption_follows_roles() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut token = Token::new();
+            assert!(token.set_fee(100, accounts.eve).is_ok());
+            assert!(token.is_max_balance_exempt(accounts.eve));
+
+            // A replaced collector is no longer exempt, unless exempted explicitly
+            assert!(token.set_fee(100, accounts.frank).is_ok());
+            assert!(!token.is_max_balance_exempt(accounts.eve));
+            assert!(token.is_max_balance_exempt(accounts.frank));
+            assert!(token.set_max_balance_exempt(accounts.frank, true).is_ok());
+            assert!(token.set_fee(100, accounts.eve).is_ok());
+            assert!(token.is_max_balance_exempt(accounts.frank));
+
+            // The same goes for a former owner
+            assert!(token.transfer_ownership(accounts.bob).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.accept_ownership().is_ok());
+            assert!(token.is_max_balance_exempt(accounts.bob));
+            assert!(!token.is_max_balance_exempt(accounts.alice));
+            assert!(token.renounce_ownership().is_ok());
+            assert!(!token.is_max_balance_exempt(accounts.bob));
+            assert!(!token.is_max_balance_exempt(ownable::RENOUNCED));
+        }
+
+        /// We test that minted and burned totals are tracked alongside the net supply.
+        #[ink::test]
+        fn minted_and_burned_totals_work() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.mint(accounts.bob, 50).is_ok());
+            assert!(token.burn(30).is_ok());
+            assert!(token.approve(accounts.charlie, 20).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(token.burn_from(accounts.alice, 20).is_ok());
+
+            assert_eq!(token.total_minted(), 150);
+            assert_eq!(token.total_burned(), 50);
+            assert_eq!(token.total_supply(), 100);
+            // The supply invariant holds
+            assert_eq!(token.total_supply(), token.total_minted() - token.total_burned());
+        }
+
+        /// We test burn_from with insufficient allowance or balance.
+        #[ink::test]
+        fn burn_from_insufficient() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.approve(accounts.bob, 500).is_ok());
+            assert!(token.approve(accounts.charlie, 10).is_ok());
+
+            // Charlie was only allowed 10 tokens
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(token.burn_from(accounts.alice, 30), Err(Error::InsufficientAllowance));
+
+            // Bob is allowed enough but Alice doesn't own enough
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.burn_from(accounts.alice, 200), Err(Error::InsufficientBalance));
+
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 500);
+            assert_eq!(token.total_supply(), 100);
+        }
+
+        /// We test a successful batch transfer to three recipients.
+        #[ink::test]
+        fn batch_transfer_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            let result = token.batch_transfer(
+                vec![accounts.bob, accounts.charlie, accounts.django],
+                vec![10, 20, 30],
+            );
+            assert!(result.is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 40);
+            assert_eq!(token.balance_of(accounts.bob), 10);
+            assert_eq!(token.balance_of(accounts.charlie), 20);
+            assert_eq!(token.balance_of(accounts.django), 30);
+            assert_eq!(token.total_supply(), 100);
+        }
+
+        /// We test batch transfer with vectors of different length.
+        #[ink::test]
+        fn batch_transfer_length_mismatch() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            let result = token.batch_transfer(vec![accounts.bob, accounts.charlie], vec![10]);
+            assert_eq!(result, Err(Error::LengthMismatch));
+            assert_eq!(token.balance_of(accounts.alice), 100);
+        }
+
+        /// We test batch transfer whose total exceeds the caller's balance.
+        #[ink::test]
+        fn batch_transfer_insufficient_balance() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            // Each leg is affordable but the sum isn't
+            let result = token.batch_transfer(vec![accounts.bob, accounts.charlie], vec![60, 60]);
+            assert_eq!(result, Err(Error::InsufficientBalance));
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.balance_of(accounts.bob), 0);
+        }
+
+        /// We test that best-effort batches apply the legs that succeed.
+        #[ink::test]
+        fn best_effort_batch_transfer_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.freeze(accounts.django).is_ok());
+
+            // No balance can cover the second leg and Django can't receive
+            let results = token.best_effort_batch_transfer(
+                vec![accounts.bob, accounts.charlie, accounts.django, accounts.eve],
+                vec![10, u128::MAX, 5, 20],
+            );
+            assert_eq!(
+                results,
+                vec![Ok(()), Err(Error::InsufficientBalance), Err(Error::AccountFrozen), Ok(())]
+            );
+            assert_eq!(token.balance_of(accounts.alice), 70);
+            assert_eq!(token.balance_of(accounts.bob), 10);
+            assert_eq!(token.balance_of(accounts.charlie), 0);
+            assert_eq!(token.balance_of(accounts.django), 0);
+            assert_eq!(token.balance_of(accounts.eve), 20);
+
+            let results = token.best_effort_batch_transfer(vec![accounts.bob, accounts.charlie], vec![10]);
+            assert_eq!(results, vec![Err(Error::LengthMismatch), Err(Error::LengthMismatch)]);
+            assert_eq!(token.balance_of(accounts.alice), 70);
+        }
+
+        /// We test that a leg whose fee can't be collected fails without moving anything.
+        #[ink::test]
+        fn best_effort_batch_transfer_checks_fee_collector() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let zero = AccountId::from([0u8; 32]);
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert_eq!(token.set_fee(100, zero), Err(Error::ZeroAddress));
+            assert!(token.set_fee(1000, accounts.eve).is_ok());
+            assert!(token.freeze(accounts.eve).is_ok());
+
+            let results = token.best_effort_batch_transfer(vec![accounts.bob], vec![50]);
+            assert_eq!(results, vec![Err(Error::AccountFrozen)]);
+
+            // A collector stored before zero was rejected is caught up front too
+            token.fee_collector = zero;
+            let results = token.best_effort_batch_transfer(vec![accounts.bob], vec![50]);
+            assert_eq!(results, vec![Err(Error::ZeroAddress)]);
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.balance_of(accounts.bob), 0);
+        }
+
+        /// We test a successful batch mint.
+        #[ink::test]
+        fn batch_mint_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let result = token.batch_mint(vec![accounts.bob, accounts.charlie], vec![10, 20]);
+            assert!(result.is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 10);
+            assert_eq!(token.balance_of(accounts.charlie), 20);
+            assert_eq!(token.total_supply(), 30);
+
+            // Only the owner can batch mint
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = token.batch_mint(vec![accounts.bob], vec![10]);
+            assert_eq!(result, Err(Error::NotOwner));
+        }
+
+        /// We test that batch mint rejects length mismatches, cap and overflow up front.
+        #[ink::test]
+        fn batch_mint_rejects_before_applying() {
+            let mut token = Token::new_with_cap(100);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let result = token.batch_mint(vec![accounts.bob], vec![10, 20]);
+            assert_eq!(result, Err(Error::LengthMismatch));
+
+            let result = token.batch_mint(vec![accounts.bob, accounts.charlie], vec![60, 50]);
+            assert_eq!(result, Err(Error::CapExceeded));
+
+            let result = token.batch_mint(vec![accounts.bob, accounts.charlie], vec![u128::MAX, 1]);
+            assert_eq!(result, Err(Error::Overflow));
+
+            assert_eq!(token.balance_of(accounts.bob), 0);
+            assert_eq!(token.total_supply(), 0);
+        }
+
+        /// We test that the holder count follows balances in and out of zero.
+        #[ink::test]
+        fn holder_count_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token.holder_count(), 0);
+
+            // Minting to a new account and again to the same account
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert_eq!(token.holder_count(), 1);
+
+            // A partial transfer to a new recipient adds a holder
+            assert!(token.transfer(accounts.bob, 50, Vec::new()).is_ok());
+            assert_eq!(token.holder_count(), 2);
+
+            // Burning a whole balance removes a holder
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.burn(50).is_ok());
+            assert_eq!(token.holder_count(), 1);
+
+            // A delegated transfer emptying the sender into a new recipient
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 150).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer_from(accounts.alice, accounts.charlie, 150, Vec::new()).is_ok());
+            assert_eq!(token.holder_count(), 1);
+        }
+
+        /// We test self-transfers and transfers that zero out the sender.
+        #[ink::test]
+        fn holder_count_transitions() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            // Self-transfers of part or all of the balance change nothing
+            assert!(token.transfer(accounts.alice, 40, Vec::new()).is_ok());
+            assert!(token.transfer(accounts.alice, 100, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.holder_count(), 1);
+
+            // Emptying the sender into a brand-new recipient keeps the count
+            assert!(token.transfer(accounts.bob, 100, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 0);
+            assert_eq!(token.holder_count(), 1);
+
+            // Zero-amount transfers don't create holders
+            assert!(token.transfer(accounts.charlie, 0, Vec::new()).is_ok());
+            assert_eq!(token.holder_count(), 1);
+        }
+
+        /// We test that a frozen account can neither send nor receive.
+        #[ink::test]
+        fn freeze_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.mint(accounts.bob, 100).is_ok());
+
+            // Only the owner can freeze
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.freeze(accounts.alice), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.freeze(accounts.bob).is_ok());
+            assert!(token.is_frozen(accounts.bob));
+
+            // Bob can't receive
+            assert_eq!(token.transfer(accounts.bob, 10, Vec::new()), Err(Error::AccountFrozen.into()));
+
+            // Bob can't send, burn or be spent from
+            assert!(token.approve(accounts.charlie, 10).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.approve(accounts.charlie, 10).is_ok());
+            assert_eq!(token.transfer(accounts.alice, 10, Vec::new()), Err(Error::AccountFrozen.into()));
+            assert_eq!(token.burn(10), Err(Error::AccountFrozen));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                token.transfer_from(accounts.bob, accounts.charlie, 10, Vec::new()),
+                Err(Error::AccountFrozen.into())
+            );
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.bob, 10, Vec::new()),
+                Err(Error::AccountFrozen.into())
+            );
+            assert_eq!(token.balance_of(accounts.bob), 100);
+        }
+
+        /// We test that unfreezing restores sending and receiving.
+        #[ink::test]
+        fn unfreeze_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.freeze(accounts.bob).is_ok());
+            assert!(token.unfreeze(accounts.bob).is_ok());
+            assert!(!token.is_frozen(accounts.bob));
+
+            assert!(token.transfer(accounts.bob, 10, Vec::new()).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer(accounts.alice, 5, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 5);
+        }
+
+        /// We test that the memo round-trips through the emitted event.
+        #[ink::test]
+        fn transfer_with_memo_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            let memo = [7u8; 32];
+            assert!(token.transfer_with_memo(accounts.bob, 30, memo).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 30);
+
+            // Mint, transfer and memo events
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 3);
+            let transferred = <TransferWithMemo as scale::Decode>::decode(&mut &events[2].data[..])
+                .expect("decoding failed");
+            assert_eq!(transferred.from, accounts.alice);
+            assert_eq!(transferred.to, accounts.bob);
+            assert_eq!(transferred.value, 30);
+            assert_eq!(transferred.memo, memo);
+        }
+
+        /// We test that the returned balances match the balances after the transfer.
+        #[ink::test]
+        fn transfer_returning_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.mint(accounts.bob, 5).is_ok());
+
+            let balances = token.transfer_returning(accounts.bob, 30);
+            assert_eq!(balances, Ok((70, 35)));
+            assert_eq!(balances, Ok((token.balance_of(accounts.alice), token.balance_of(accounts.bob))));
+
+            // Fees are deducted before the balances are read
+            assert!(token.set_fee(1000, accounts.eve).is_ok());
+            assert_eq!(token.transfer_returning(accounts.bob, 50), Ok((20, 80)));
+            assert_eq!(token.balance_of(accounts.eve), 5);
+            assert_eq!(
+                token.transfer_returning(accounts.bob, 21),
+                Err(Error::InsufficientBalance)
+            );
+        }
+
+        /// We test that the transfer fee is charged on every path that moves tokens.
+        #[ink::test]
+        fn fee_applies_to_every_transfer_path() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 1000).is_ok());
+            assert!(token.set_fee(1000, accounts.eve).is_ok());
+
+            // The spender's allowance covers the fee as well
+            assert!(token.approve(accounts.bob, 100).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer_from(accounts.alice, accounts.charlie, 100, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.charlie), 90);
+            assert_eq!(token.balance_of(accounts.eve), 10);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.batch_transfer(vec![accounts.bob, accounts.charlie], vec![50, 100]).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 45);
+            assert_eq!(token.balance_of(accounts.charlie), 180);
+            assert_eq!(token.balance_of(accounts.eve), 25);
+
+            // Only what is left after the fee gets locked
+            assert!(token.transfer_locked(accounts.django, 100, 1000).is_ok());
+            assert_eq!(token.locked_balance_of(accounts.django), 90);
+            assert_eq!(token.balance_of(accounts.eve), 35);
+            assert_eq!(token.balance_of(accounts.alice), 650);
+        }
+
+        /// We test that a safe transfer to a plain account behaves like a transfer.
+        #[ink::test]
+        fn safe_transfer_to_account_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            assert!(token.safe_transfer(accounts.bob, 30, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 70);
+            assert_eq!(token.balance_of(accounts.bob), 30);
+
+            // Balance checks still apply
+            assert_eq!(
+                token.safe_transfer(accounts.bob, 1000, Vec::new()),
+                Err(Error::InsufficientBalance)
+            );
+        }
+
+        /// We test that approving and calling an account that is not a contract fails untouched.
+        #[ink::test]
+        fn approve_and_call_requires_contract() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            assert_eq!(
+                token.approve_and_call(accounts.bob, 30, Vec::new()),
+                Err(Error::CallbackFailed)
+            );
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+        }
+
+        /// We test that only matured locks are released into the spendable balance.
+        #[ink::test]
+        fn release_locks_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            assert!(token.transfer_locked(accounts.bob, 30, 1000).is_ok());
+            assert!(token.transfer_locked(accounts.bob, 20, 2000).is_ok());
+            assert_eq!(token.balance_of(accounts.alice), 50);
+            assert_eq!(token.balance_of(accounts.bob), 0);
+            assert_eq!(token.locked_balance_of(accounts.bob), 50);
+            assert_eq!(token.total_supply(), 100);
+            assert_eq!(
+                token.transfer_locked(accounts.bob, 100, 1000),
+                Err(Error::InsufficientBalance)
+            );
+
+            // Nothing has matured yet
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.release_locks(), Ok(0));
+
+            // Only the first lock has matured
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1500);
+            assert_eq!(token.release_locks(), Ok(30));
+            assert_eq!(token.balance_of(accounts.bob), 30);
+            assert_eq!(token.locked_balance_of(accounts.bob), 20);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2000);
+            assert_eq!(token.release_locks(), Ok(20));
+            assert_eq!(token.balance_of(accounts.bob), 50);
+            assert_eq!(token.locked_balance_of(accounts.bob), 0);
+        }
+
+        /// We test that an account can't be flooded with locks.
+        #[ink::test]
+        fn transfer_locked_is_capped() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            for _ in 0..MAX_LOCKS {
+                assert!(token.transfer_locked(accounts.bob, 1, 1000).is_ok());
+            }
+            assert_eq!(token.transfer_locked(accounts.bob, 1, 1000), Err(Error::TooManyLocks));
+            assert_eq!(token.balance_of(accounts.alice), 100 - MAX_LOCKS as u128);
+
+            // Releasing matured locks makes room again
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.release_locks(), Ok(MAX_LOCKS as u128));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.transfer_locked(accounts.bob, 1, 2000).is_ok());
+        }
+
+        /// We test that empty and overly long locks are rejected.
+        #[ink::test]
+        fn transfer_locked_rejects_empty_and_unbounded() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+
+            assert_eq!(token.transfer_locked(accounts.bob, 0, 2000), Err(Error::ZeroAmount));
+            assert_eq!(
+                token.transfer_locked(accounts.bob, 10, 1001 + MAX_LOCK_DURATION),
+                Err(Error::LockTooLong)
+            );
+            assert_eq!(token.transfer_locked(accounts.bob, 10, u64::MAX), Err(Error::LockTooLong));
+            assert_eq!(token.locked_balance_of(accounts.bob), 0);
+            assert_eq!(token.balance_of(accounts.alice), 100);
+
+            // The longest allowed lock still goes through
+            assert!(token.transfer_locked(accounts.bob, 10, 1000 + MAX_LOCK_DURATION).is_ok());
+            assert_eq!(token.locked_balance_of(accounts.bob), 10);
+        }
+
+        /// We test that a recipient can send unwanted locks back to their sender.
+        #[ink::test]
+        fn reject_locks_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.mint(accounts.charlie, 100).is_ok());
+            for _ in 0..MAX_LOCKS - 1 {
+                assert!(token.transfer_locked(accounts.bob, 1, 1000).is_ok());
+            }
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(token.transfer_locked(accounts.bob, 20, 1000).is_ok());
+            assert_eq!(token.transfer_locked(accounts.bob, 1, 1000), Err(Error::TooManyLocks));
+
+            // Bob clears Alice's locks and keeps Charlie's
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.reject_locks(accounts.alice), Ok(MAX_LOCKS as u128 - 1));
+            assert_eq!(token.reject_locks(accounts.alice), Ok(0));
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.locked_balance_of(accounts.bob), 20);
+            assert_eq!(token.total_supply(), 200);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(token.transfer_locked(accounts.bob, 1, 1000).is_ok());
+        }
+
+        /// We test that topping up a balance with pending locks can't get it past the maximum.
+        #[ink::test]
+        fn locks_count_towards_max_balance() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.set_max_balance(50).is_ok());
+            assert!(token.transfer_locked(accounts.bob, 30, 1000).is_ok());
+
+            // Only the room left next to the lock can be transferred or minted
+            assert_eq!(
+                token.transfer(accounts.bob, 30, Vec::new()),
+                Err(Error::MaxBalanceExceeded.into())
+            );
+            assert_eq!(token.mint(accounts.bob, 30), Err(Error::MaxBalanceExceeded));
+            assert!(token.transfer(accounts.bob, 20, Vec::new()).is_ok());
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.release_locks(), Ok(30));
+            assert_eq!(token.balance_of(accounts.bob), 50);
+        }
+
+        /// We test that voting power follows self-delegation and balance changes.
+        #[ink::test]
+        fn self_delegation_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            // Balances carry no votes until delegated
+            assert_eq!(token.get_votes(accounts.alice), 0);
+            token.delegate(accounts.alice);
+            assert_eq!(token.delegates(accounts.alice), Some(accounts.alice));
+            assert_eq!(token.get_votes(accounts.alice), 100);
+
+            // Transfers move votes to the receiver's delegate
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            token.delegate(accounts.bob);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.transfer(accounts.bob, 30, Vec::new()).is_ok());
+            assert_eq!(token.get_votes(accounts.alice), 70);
+            assert_eq!(token.get_votes(accounts.bob), 30);
+
+            // Burning removes votes
+            assert!(token.burn(20).is_ok());
+            assert_eq!(token.get_votes(accounts.alice), 50);
+        }
+
+        /// We test that re-delegating moves the whole balance to the new delegate.
+        #[ink::test]
+        fn redelegation_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            token.delegate(accounts.bob);
+            assert_eq!(token.get_votes(accounts.bob), 100);
+            token.delegate(accounts.charlie);
+            assert_eq!(token.get_votes(accounts.bob), 0);
+            assert_eq!(token.get_votes(accounts.charlie), 100);
+            assert_eq!(token.get_votes(accounts.alice), 0);
+        }
+
+        /// We test looking up votes as of past blocks across two checkpoints.
+        #[ink::test]
+        fn get_past_votes_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            token.delegate(accounts.alice);
+
+            // First checkpoint in block 1, second in block 3
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert!(token.burn(40).is_ok());
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(token.get_past_votes(accounts.alice, 0), Ok(0));
+            assert_eq!(token.get_past_votes(accounts.alice, 1), Ok(100));
+            assert_eq!(token.get_past_votes(accounts.alice, 2), Ok(100));
+            assert_eq!(token.get_past_votes(accounts.alice, 3), Ok(60));
+            assert_eq!(token.get_past_votes(accounts.alice, 4), Err(Error::BlockNotYetMined));
+        }
+
+        /// We test that balances are preserved as of a snapshot.
+        #[ink::test]
+        fn snapshot_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert_eq!(token.balance_of_at(accounts.alice, 1), None);
+
+            // Only the owner can take snapshots
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.snapshot(), Err(Error::NotOwner));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token.snapshot(), Ok(1));
+
+            // Unchanged balances read through to the current balance
+            assert_eq!(token.balance_of_at(accounts.alice, 1), Some(100));
+
+            // A transfer after the snapshot keeps the snapshotted balances
+            assert!(token.transfer(accounts.bob, 30, Vec::new()).is_ok());
+            assert!(token.transfer(accounts.bob, 20, Vec::new()).is_ok());
+            assert_eq!(token.balance_of_at(accounts.alice, 1), Some(100));
+            assert_eq!(token.balance_of_at(accounts.bob, 1), Some(0));
+
+            // A second snapshot sees the balances after the transfers
+            assert_eq!(token.snapshot(), Ok(2));
+            assert!(token.transfer(accounts.bob, 50, Vec::new()).is_ok());
+            assert_eq!(token.balance_of_at(accounts.alice, 1), Some(100));
+            assert_eq!(token.balance_of_at(accounts.alice, 2), Some(50));
+            assert_eq!(token.balance_of_at(accounts.bob, 2), Some(50));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+        }
+
+        /// We test snapshot lookups across many snapshots with few balance changes.
+        #[ink::test]
+        fn balance_of_at_finds_the_next_record() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            // Alice's balance changes only after snapshots 3 and 7
+            for id in 1..=10 {
+                assert_eq!(token.snapshot(), Ok(id));
+                if id == 3 || id == 7 {
+                    assert!(token.transfer(accounts.bob, 10, Vec::new()).is_ok());
+                }
+            }
+            assert_eq!(token.snapshot_record_count.get(accounts.alice), Some(2));
+            for id in 1..=3 {
+                assert_eq!(token.balance_of_at(accounts.alice, id), Some(100));
+            }
+            for id in 4..=7 {
+                assert_eq!(token.balance_of_at(accounts.alice, id), Some(90));
+            }
+            for id in 8..=10 {
+                assert_eq!(token.balance_of_at(accounts.alice, id), Some(80));
+            }
+            assert_eq!(token.balance_of_at(accounts.bob, 5), Some(10));
+            assert_eq!(token.balance_of_at(accounts.alice, 11), None);
+        }
+
+        /// We test that a guarded path can't be entered while already running.
+        #[ink::test]
+        fn reentrancy_guard_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.approve(accounts.alice, 100).is_ok());
+
+            // Simulate a nested call into transfer_from while the lock is held
+            let result = token.non_reentrant(|token| {
+                token.transfer_with_allowance(accounts.alice, accounts.bob, 10)
+            });
+            assert_eq!(result, Err(Error::Reentrancy));
+            assert_eq!(token.balance_of(accounts.bob), 0);
+
+            // The lock is released afterwards
+            assert!(!token.locked);
+            assert!(token.transfer_from(accounts.alice, accounts.bob, 10, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 10);
+        }
+
+        /// We test that the transfer fee is rounded down and sent to the collector.
+        #[ink::test]
+        fn transfer_fee_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 1000).is_ok());
+            assert_eq!(token.set_fee(10_001, accounts.eve), Err(Error::InvalidFee));
+            assert!(token.set_fee(100, accounts.eve).is_ok());
+
+            // 1% of 250 is 2.5, rounded down to 2
+            assert!(token.transfer(accounts.bob, 250, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 248);
+            assert_eq!(token.balance_of(accounts.eve), 2);
+
+            // 1% of 99 rounds down to no fee at all
+            assert!(token.transfer(accounts.bob, 99, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 347);
+            assert_eq!(token.balance_of(accounts.eve), 2);
+            assert_eq!(token.balance_of(accounts.alice), 651);
+
+            // Mint, two legs for the taxed transfer, one for the untaxed one
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 4);
+            let fee = <Transfer as scale::Decode>::decode(&mut &events[2].data[..])
+                .expect("decoding failed");
+            assert_eq!(fee.to, Some(accounts.eve));
+            assert_eq!(fee.value, 2);
+        }
+
+        /// We test that a zero fee behaves like a plain transfer.
+        #[ink::test]
+        fn zero_transfer_fee_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.mint(accounts.alice, 100).is_ok());
+            assert!(token.set_fee(0, accounts.eve).is_ok());
+
+            assert!(token.transfer(accounts.bob, 100, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(token.balance_of(accounts.eve), 0);
+            assert_eq!(ink::env::test::recorded_events().count(), 2);
+
+            // Only the owner configures fees
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.set_fee(100, accounts.bob), Err(Error::NotOwner));
+        }
+
+        /// We test that a taxed transfer shares the reflection among holders by balance.
+        #[ink::test]
+        fn reflection_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            // The contract account holds reflections until they are withdrawn
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut token = Token::new();
+            assert!(token.mint(accounts.bob, 340).is_ok());
+            assert!(token.set_reflection(2000).is_ok());
+
+            // 20% of 100 is skimmed and shared by 240 and 80 tokens held afterwards
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer(accounts.charlie, 100, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.charlie), 80);
+            assert_eq!(token.balance_of(accounts.django), 20);
+            assert_eq!(token.withdrawable_dividend(accounts.bob), 15);
+            assert_eq!(token.withdrawable_dividend(accounts.charlie), 5);
+            assert_eq!(token.withdrawable_dividend(accounts.django), 0);
+
+            // Tokens received later don't earn past reflections
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.mint(accounts.charlie, 80).is_ok());
+            assert_eq!(token.withdrawable_dividend(accounts.charlie), 5);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.withdraw_dividend(), Ok(15));
+            assert_eq!(token.balance_of(accounts.bob), 255);
+            assert_eq!(token.balance_of(accounts.django), 5);
+            assert_eq!(token.withdrawable_dividend(accounts.bob), 0);
+            assert_eq!(token.withdraw_dividend(), Ok(0));
+        }
+
+        /// We test that the reflection is charged on `transfer_from` and `batch_transfer` too.
+        #[ink::test]
+        fn reflection_applies_to_every_transfer_path() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut token = Token::new();
+            assert!(token.mint(accounts.bob, 340).is_ok());
+            assert!(token.set_reflection(2000).is_ok());
+
+            // 20% of 100 is skimmed and shared by 240 and 80 tokens held afterwards
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.approve(accounts.charlie, 100).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(token.transfer_from(accounts.bob, accounts.eve, 100, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(accounts.eve), 80);
+            assert_eq!(token.balance_of(accounts.django), 20);
+            assert_eq!(token.withdrawable_dividend(accounts.bob), 15);
+            assert_eq!(token.withdrawable_dividend(accounts.eve), 5);
+            assert_eq!(token.withdrawable_dividend(accounts.charlie), 0);
+
+            // Another 20 is shared by 140 and 160 tokens held afterwards
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.batch_transfer(vec![accounts.eve], vec![100]).is_ok());
+            assert_eq!(token.balance_of(accounts.eve), 160);
+            assert_eq!(token.balance_of(accounts.django), 40);
+            assert_eq!(token.withdrawable_dividend(accounts.bob), 24);
+            assert_eq!(token.withdrawable_dividend(accounts.eve), 15);
+        }
+
+        /// We test that deposited dividends are shared by the balances at deposit time.
+        #[ink::test]
+        fn deposit_dividends_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut token = Token::new();
+            assert!(token.mint(accounts.bob, 300).is_ok());
+            assert!(token.mint(accounts.charlie, 100).is_ok());
+            assert!(token.mint(accounts.alice, 400).is_ok());
+            assert_eq!(token.deposit_dividends(401), Err(Error::InsufficientBalance));
+
+            // Alice deposits everything she holds, so only Bob and Charlie share it
+            assert_eq!(token.deposit_dividends(400), Ok(()));
+            assert_eq!(token.balance_of(accounts.django), 400);
+            assert_eq!(token.withdrawable_dividend(accounts.alice), 0);
+
+            // Moving tokens afterwards doesn't move the share earned at deposit time
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer(accounts.charlie, 300, Vec::new()).is_ok());
+            assert_eq!(token.withdraw_dividend(), Ok(300));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(token.withdraw_dividend(), Ok(100));
+            assert_eq!(token.balance_of(accounts.charlie), 500);
+            assert_eq!(token.balance_of(accounts.django), 0);
+
+            assert_eq!(token.deposit_dividends(10), Err(Error::NotOwner));
+        }
+
+        /// We test that fee and reflection together can't exceed the transfer.
+        #[ink::test]
+        fn set_reflection_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(token.set_fee(9000, accounts.eve).is_ok());
+            assert_eq!(token.set_reflection(2000), Err(Error::InvalidFee));
+            assert!(token.set_reflection(1000).is_ok());
+            assert_eq!(token.reflection_bps(), 1000);
+            assert_eq!(token.set_fee(9001, accounts.eve), Err(Error::InvalidFee));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.set_reflection(0), Err(Error::NotOwner));
+        }
+
+        /// We test the fixed-point helpers behind reflections at the edges of `u128`.
+        #[ink::test]
+        fn magnify_works() {
+            assert_eq!(magnify(1, 3), Some(u128::from(u64::MAX) / 3));
+            assert_eq!(magnify(u128::MAX, u128::MAX), Some(1 << 64));
+            assert_eq!(magnify(1 << 64, 1), None);
+            assert_eq!(magnify(1, 0), None);
+            assert_eq!(demagnify(u128::MAX, 1 << 64), u128::MAX);
+            assert_eq!(demagnify(3, magnify(1, 3).unwrap()), 0);
+            assert_eq!(demagnify(u128::MAX, u128::MAX), u128::MAX);
+        }
+
+        /// We test claiming from a linear vesting schedule over time.
+        #[ink::test]
+        fn vesting_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            assert!(token.create_vesting(accounts.bob, 1000, 100).is_ok());
+            assert_eq!(
+                token.create_vesting(accounts.bob, 1000, 100),
+                Err(Error::VestingExists)
+            );
+
+            // Nothing is unlocked at the start
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.claim_vested(), Ok(0));
+            assert_eq!(token.balance_of(accounts.bob), 0);
+
+            // A quarter of the way through
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1025);
+            assert_eq!(token.claim_vested(), Ok(250));
+            assert_eq!(token.balance_of(accounts.bob), 250);
+
+            // Halfway through only the newly unlocked portion is claimed
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1050);
+            assert_eq!(token.claim_vested(), Ok(250));
+            assert_eq!(token.balance_of(accounts.bob), 500);
+
+            // Past the end everything is unlocked, once
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2000);
+            assert_eq!(token.claim_vested(), Ok(500));
+            assert_eq!(token.claim_vested(), Ok(0));
+            assert_eq!(token.balance_of(accounts.bob), 1000);
+            assert_eq!(token.total_supply(), 1000);
+            assert_eq!(token.vesting_of(accounts.bob).map(|schedule| schedule.claimed), Some(1000));
+        }
+
+        /// We test that vesting is owner-only and claims need a schedule.
+        #[ink::test]
+        fn vesting_requires_schedule() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.create_vesting(accounts.bob, 1000, 100), Err(Error::NotOwner));
+            assert_eq!(token.claim_vested(), Err(Error::NoVestingSchedule));
+        }
+
+        /// Hash helper mirroring how the contract builds merkle nodes.
+        fn hash<T: scale::Encode>(value: &T) -> [u8; 32] {
+            let mut output = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(value, &mut output);
+            output
+        }
+
+        /// We test airdrop claims against a hand-built 2-leaf tree.
+        #[ink::test]
+        fn merkle_claim_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let bob_leaf = hash(&(accounts.bob, 100u128));
+            let charlie_leaf = hash(&(accounts.charlie, 200u128));
+            let root = if bob_leaf <= charlie_leaf {
+                hash(&(bob_leaf, charlie_leaf))
+            } else {
+                hash(&(charlie_leaf, bob_leaf))
+            };
+            assert!(token.set_merkle_root(root).is_ok());
+
+            // Bob claims with Charlie's leaf as proof
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.claim(100, vec![charlie_leaf]).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert!(token.is_claimed(accounts.bob));
+
+            // A second claim is rejected
+            assert_eq!(token.claim(100, vec![charlie_leaf]), Err(Error::AlreadyClaimed));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+        }
+
+        /// We test that tampered proofs and amounts are rejected.
+        #[ink::test]
+        fn merkle_claim_invalid_proof() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let bob_leaf = hash(&(accounts.bob, 100u128));
+            let charlie_leaf = hash(&(accounts.charlie, 200u128));
+            let root = if bob_leaf <= charlie_leaf {
+                hash(&(bob_leaf, charlie_leaf))
+            } else {
+                hash(&(charlie_leaf, bob_leaf))
+            };
+            assert!(token.set_merkle_root(root).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let mut tampered = bob_leaf;
+            tampered[0] ^= 1;
+            assert_eq!(token.claim(200, vec![tampered]), Err(Error::InvalidProof));
+            // Claiming a different amount doesn't match the leaf either
+            assert_eq!(token.claim(300, vec![bob_leaf]), Err(Error::InvalidProof));
+            assert!(!token.is_claimed(accounts.charlie));
+
+            assert!(token.claim(200, vec![bob_leaf]).is_ok());
+            assert_eq!(token.balance_of(accounts.charlie), 200);
+        }
+
+        /// We test that the faucet enforces its cooldown per account.
+        #[ink::test]
+        fn faucet_works() {
+            let mut token = Token::new_with_faucet(50, 1000);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(5000);
+
+            // First claim
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.faucet().is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 50);
+
+            // Early second claim, while other accounts can still claim
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(5999);
+            assert_eq!(token.faucet(), Err(Error::CooldownActive));
+            assert_eq!(token.balance_of(accounts.bob), 50);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(token.faucet().is_ok());
+
+            // Claim after the cooldown
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(6000);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.faucet().is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(token.total_supply(), 150);
+        }
+
+        /// We test that tokens built without a faucet don't drip.
+        #[ink::test]
+        fn faucet_disabled() {
+            let mut token = Token::new();
+            assert_eq!(token.drip_amount(), 0);
+            assert_eq!(token.faucet(), Err(Error::FaucetDisabled));
+        }
+
+        /// We test wrapping native value and unwrapping it again.
+        #[ink::test]
+        fn deposit_and_withdraw_work() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut token = Token::new();
+            let native_balance = |account| {
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                    .expect("no balance")
+            };
+            let contract_native = native_balance(accounts.django);
+            let bob_native = native_balance(accounts.bob);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::transfer_in::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(token.deposit(), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(token.total_supply(), 100);
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(token.withdraw(101), Err(Error::InsufficientBalance));
+            assert_eq!(token.withdraw(40), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 60);
+            assert_eq!(token.total_supply(), 60);
+            assert_eq!(native_balance(accounts.django), contract_native + 60);
+            assert_eq!(native_balance(accounts.bob), bob_native - 60);
+        }
+
+        /// We test that the contract reports its own account and native balance.
+        #[ink::test]
+        fn contract_account_and_balance_work() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut token = Token::new();
+            assert_eq!(token.contract_account(), accounts.django);
+            let before = token.contract_balance();
+            assert_eq!(
+                Some(before),
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.django).ok()
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::transfer_in::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(token.deposit(), Ok(()));
+            assert_eq!(token.contract_balance(), before + 100);
+        }
+
+        /// We test that the owner can only pull native value out while paused.
+        #[ink::test]
+        fn emergency_withdraw_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.django);
+            let mut token = Token::new();
+            let native_balance = |account| {
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                    .expect("no balance")
+            };
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::transfer_in::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(token.deposit(), Ok(()));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            let contract_native = native_balance(accounts.django);
+            let charlie_native = native_balance(accounts.charlie);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token.emergency_withdraw(accounts.charlie, 30), Err(Error::NotPaused));
+            assert!(token.pause().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.emergency_withdraw(accounts.bob, 30), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token.emergency_withdraw(accounts.charlie, 30), Ok(()));
+            assert_eq!(native_balance(accounts.django), contract_native - 30);
+            assert_eq!(native_balance(accounts.charlie), charlie_native + 30);
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let withdrawal = <EmergencyWithdrawal as scale::Decode>::decode(&mut &events.last().unwrap().data[..])
+                .expect("decoding failed");
+            assert_eq!(withdrawal.to, accounts.charlie);
+            assert_eq!(withdrawal.amount, 30);
+        }
+
+        /// We test redeeming vouchers signed by the signer's key.
+        #[ink::test]
+        fn redeem_voucher_works() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let signer = ink_e2e::alice();
+            assert!(token.set_signer(signer.public_key().0).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let contract = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            let voucher = signer.sign(&scale::Encode::encode(&(contract, accounts.bob, 100u128, 7u64)));
+            assert!(token.redeem_voucher(100, 7, voucher.0).is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert!(token.is_nonce_used(7));
+
+            // The same voucher can't be redeemed twice
+            assert_eq!(token.redeem_voucher(100, 7, voucher.0), Err(Error::NonceUsed));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+        }
+
+        /// We test that vouchers not signed by the signer for these terms are rejected.
+        #[ink::test]
+        fn redeem_voucher_invalid_signature() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let signer = ink_e2e::alice();
+            let contract = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            let message = scale::Encode::encode(&(contract, accounts.bob, 100u128, 1u64));
+            let voucher = signer.sign(&message);
+
+            // No signer has been set yet
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.redeem_voucher(100, 1, voucher.0), Err(Error::InvalidSignature));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.set_signer(signer.public_key().0).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            // Signed by another key, for another amount, or for another caller
+            let forged = ink_e2e::bob().sign(&message);
+            assert_eq!(token.redeem_voucher(100, 1, forged.0), Err(Error::InvalidSignature));
+            assert_eq!(token.redeem_voucher(200, 1, voucher.0), Err(Error::InvalidSignature));
+            // Signed for another token contract
+            let elsewhere = signer.sign(&scale::Encode::encode(&(accounts.django, accounts.bob, 100u128, 1u64)));
+            assert_eq!(token.redeem_voucher(100, 1, elsewhere.0), Err(Error::InvalidSignature));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(token.redeem_voucher(100, 1, voucher.0), Err(Error::InvalidSignature));
+            assert!(!token.is_nonce_used(1));
+            assert_eq!(token.total_supply(), 0);
+        }
+
+        /// We test that the rescue is owner-only and can't touch this token's balances.
+        #[ink::test]
+        fn rescue_tokens_guards() {
+            let mut token = Token::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let own = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            assert_eq!(
+                token.rescue_tokens(own, accounts.bob, 10),
+                Err(Error::CannotRescueOwnToken)
+            );
 
-    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
-    /// module and test functions are marked with a `#[test]` attribute.
-    /// The below code is technically just normal Rust code.
-    #[cfg(test)]
-    mod tests {
-        /// Imports all the definitions from the outer scope so we can use them here.
-        use super::*;
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(token.rescue_tokens(accounts.django, accounts.bob, 10), Err(Error::NotOwner));
+        }
 
-        /// We test if the default constructor does its job.
+        /// We test a relayed transfer signed by the sender's account key.
         #[ink::test]
-        fn default_works() {
-            let token = Token::default();
-            // Test that a new account has zero balance
+        fn transfer_with_sig_works() {
+            let mut token = Token::new();
             let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
-            assert_eq!(token.balance_of(accounts.alice), 0);
+            let signer = ink_e2e::alice();
+            let from = AccountId::from(signer.public_key().0);
+            assert!(token.mint(from, 100).is_ok());
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            let contract = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            let intent = scale::Encode::encode(&(contract, from, accounts.charlie, 40u128, 0u64, 1000u64));
+            let signature = signer.sign(&intent).0;
+
+            // Bob relays the transfer
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token.transfer_with_sig(from, accounts.charlie, 40, 0, 1000, signature).is_ok());
+            assert_eq!(token.balance_of(from), 60);
+            assert_eq!(token.balance_of(accounts.charlie), 40);
+            assert_eq!(token.balance_of(accounts.bob), 0);
+            assert!(token.is_transfer_nonce_used(from, 0));
+            assert_eq!(
+                token.transfer_with_sig(from, accounts.charlie, 40, 0, 1000, signature),
+                Err(Error::NonceUsed)
+            );
         }
 
-        /// We test minting functionality.
+        /// We test that expired, altered or foreign signed transfers are rejected.
         #[ink::test]
-        fn mint_works() {
+        fn transfer_with_sig_rejects_invalid() {
             let mut token = Token::new();
             let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
-            
-            // Mint 100 tokens to Alice
-            token.mint(accounts.alice, 100);
-            assert_eq!(token.balance_of(accounts.alice), 100);
-            
-            // Mint more tokens to Alice
-            token.mint(accounts.alice, 50);
-            assert_eq!(token.balance_of(accounts.alice), 150);
+            let signer = ink_e2e::alice();
+            let from = AccountId::from(signer.public_key().0);
+            assert!(token.mint(from, 100).is_ok());
+            let contract = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            let intent = scale::Encode::encode(&(contract, from, accounts.charlie, 40u128, 0u64, 1000u64));
+            let signature = signer.sign(&intent).0;
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+
+            // Past the deadline
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1001);
+            assert_eq!(
+                token.transfer_with_sig(from, accounts.charlie, 40, 0, 1000, signature),
+                Err(Error::Expired)
+            );
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+
+            // The relayer changed the amount or the recipient
+            assert_eq!(
+                token.transfer_with_sig(from, accounts.charlie, 50, 0, 1000, signature),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(
+                token.transfer_with_sig(from, accounts.bob, 40, 0, 1000, signature),
+                Err(Error::InvalidSignature)
+            );
+
+            // Signed by someone other than `from`
+            let forged = ink_e2e::bob().sign(&intent).0;
+            assert_eq!(
+                token.transfer_with_sig(from, accounts.charlie, 40, 0, 1000, forged),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(token.balance_of(from), 100);
+            assert!(!token.is_transfer_nonce_used(from, 0));
         }
 
-        /// We test transfer functionality.
+        /// We test that the zero account is rejected as a recipient by default.
         #[ink::test]
-        fn transfer_works() {
+        fn zero_address_rejected() {
             let mut token = Token::new();
             let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
-            
-            // Mint tokens to Alice
-            token.mint(accounts.alice, 100);
+            let zero = AccountId::from([0u8; 32]);
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            assert_eq!(token.mint(zero, 10), Err(Error::ZeroAddress));
+            assert_eq!(
+                token.transfer(zero, 10, Vec::new()),
+                Err(PSP22Error::ZeroRecipientAddress)
+            );
+            assert!(token.approve(accounts.bob, 10).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, zero, 10, Vec::new()),
+                Err(PSP22Error::ZeroRecipientAddress)
+            );
+            assert_eq!(token.balance_of(zero), 0);
             assert_eq!(token.balance_of(accounts.alice), 100);
-            assert_eq!(token.balance_of(accounts.bob), 0);
-            
-            // Set Alice as the caller for the transfer
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
-            
-            // Transfer 30 tokens from Alice to Bob
-            let result = token.transfer(accounts.bob, 30);
-            assert!(result.is_ok());
-            assert_eq!(token.balance_of(accounts.alice), 70);
-            assert_eq!(token.balance_of(accounts.bob), 30);
         }
 
-        /// We test transfer with insufficient balance.
+        /// We test that the zero account can be used as a burn sink when allowed.
         #[ink::test]
-        fn transfer_insufficient_balance() {
-            let mut token = Token::new();
+        fn zero_address_allowed() {
+            let mut token = Token::new_with_zero_address(true);
             let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
-            
-            // Set Alice as the caller (she has no balance)
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
-            
-            // Try to transfer more than Alice has
-            let result = token.transfer(accounts.bob, 100);
-            assert!(result.is_err());
-            assert_eq!(result.unwrap_err(), Error::InsufficientBalance);
+            let zero = AccountId::from([0u8; 32]);
+            assert!(token.mint(accounts.alice, 100).is_ok());
+
+            assert!(token.mint(zero, 10).is_ok());
+            assert!(token.transfer(zero, 10, Vec::new()).is_ok());
+            assert_eq!(token.balance_of(zero), 20);
         }
     }
 
@@ -165,6 +4589,9 @@ mod token {
         /// A helper function used for calling contract messages.
         use ink_e2e::ContractsBackend;
 
+        /// Used for querying native balances.
+        use ink_e2e::ChainBackend;
+
         /// The End-to-End test `Result` type.
         type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -218,6 +4645,34 @@ mod token {
             Ok(())
         }
 
+        /// We test that the total supply reflects minted tokens.
+        #[ink_e2e::test]
+        async fn total_supply_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let mut constructor = TokenRef::new();
+            let contract = client
+                .instantiate("token", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Token>();
+
+            // When - mint 100 tokens to Bob
+            let mint = call_builder.mint(ink_e2e::bob().account_id(), 100);
+            let _mint_result = client
+                .call(&ink_e2e::alice(), &mint)
+                .submit()
+                .await
+                .expect("mint failed");
+
+            // Then - check the total supply
+            let total_supply = call_builder.total_supply();
+            let total_supply_result = client.call(&ink_e2e::alice(), &total_supply).dry_run().await?;
+            assert_eq!(total_supply_result.return_value(), 100);
+
+            Ok(())
+        }
+
         /// We test that we can transfer tokens between accounts.
         #[ink_e2e::test]
         async fn transfer_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
@@ -239,7 +4694,7 @@ mod token {
                 .expect("mint failed");
 
             // When - transfer 30 tokens from Alice to Bob
-            let transfer = call_builder.transfer(ink_e2e::bob().account_id(), 30);
+            let transfer = call_builder.transfer(ink_e2e::bob().account_id(), 30, Vec::new());
             let _transfer_result = client
                 .call(&ink_e2e::alice(), &transfer)
                 .submit()
@@ -257,5 +4712,324 @@ mod token {
 
             Ok(())
         }
+
+        /// We test that a safe transfer reaches contracts with the hook and respects rejections.
+        #[ink_e2e::test]
+        async fn safe_transfer_to_contract_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let mut constructor = TokenRef::new();
+            let contract = client
+                .instantiate("token", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Token>();
+
+            let mut accepting = mock_receiver::mock_receiver::MockReceiverRef::new(true);
+            let accepting = client
+                .instantiate("mock_receiver", &ink_e2e::alice(), &mut accepting)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut rejecting = mock_receiver::mock_receiver::MockReceiverRef::new(false);
+            let rejecting = client
+                .instantiate("mock_receiver", &ink_e2e::alice(), &mut rejecting)
+                .submit()
+                .await
+                .expect("instantiate failed");
+
+            // Mint tokens to Alice
+            let mint = call_builder.mint(ink_e2e::alice().account_id(), 100);
+            let _mint_result = client
+                .call(&ink_e2e::alice(), &mint)
+                .submit()
+                .await
+                .expect("mint failed");
+
+            // When - send tokens to the accepting receiver
+            let safe_transfer = call_builder.safe_transfer(accepting.account_id, 30, Vec::new());
+            let _transfer_result = client
+                .call(&ink_e2e::alice(), &safe_transfer)
+                .submit()
+                .await
+                .expect("safe transfer failed");
+
+            // Then - the tokens arrived
+            let balance_of = call_builder.balance_of(accepting.account_id);
+            let balance_result = client.call(&ink_e2e::alice(), &balance_of).dry_run().await?;
+            assert_eq!(balance_result.return_value(), 30);
+
+            // When - send tokens to the rejecting receiver
+            let safe_transfer = call_builder.safe_transfer(rejecting.account_id, 30, Vec::new());
+            let transfer_result = client.call(&ink_e2e::alice(), &safe_transfer).dry_run().await?;
+
+            // Then - the transfer is rejected
+            assert_eq!(transfer_result.return_value(), Err(Error::TransferRejected));
+
+            Ok(())
+        }
+
+        /// We test that a spender contract can consume its allowance inside the approval hook.
+        #[ink_e2e::test]
+        async fn approve_and_call_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let mut constructor = TokenRef::new();
+            let contract = client
+                .instantiate("token", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Token>();
+
+            let mut accepting = mock_receiver::mock_receiver::MockReceiverRef::new(true);
+            let accepting = client
+                .instantiate("mock_receiver", &ink_e2e::alice(), &mut accepting)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut rejecting = mock_receiver::mock_receiver::MockReceiverRef::new(false);
+            let rejecting = client
+                .instantiate("mock_receiver", &ink_e2e::alice(), &mut rejecting)
+                .submit()
+                .await
+                .expect("instantiate failed");
+
+            let alice = ink_e2e::alice().account_id();
+            let mint = call_builder.mint(alice, 100);
+            let _mint_result = client
+                .call(&ink_e2e::alice(), &mint)
+                .submit()
+                .await
+                .expect("mint failed");
+
+            // When - approve the accepting spender, which pulls the tokens in its hook
+            let approve_and_call = call_builder.approve_and_call(accepting.account_id, 30, Vec::new());
+            let _approve_result = client
+                .call(&ink_e2e::alice(), &approve_and_call)
+                .submit()
+                .await
+                .expect("approve and call failed");
+
+            // Then - the allowance was consumed
+            let balance_of = call_builder.balance_of(accepting.account_id);
+            let balance_result = client.call(&ink_e2e::alice(), &balance_of).dry_run().await?;
+            assert_eq!(balance_result.return_value(), 30);
+            let allowance = call_builder.allowance(alice, accepting.account_id);
+            let allowance_result = client.call(&ink_e2e::alice(), &allowance).dry_run().await?;
+            assert_eq!(allowance_result.return_value(), 0);
+
+            // And - the state written by the reentrant transfer survived the hook
+            let holder_count = call_builder.holder_count();
+            let holder_count_result = client.call(&ink_e2e::alice(), &holder_count).dry_run().await?;
+            assert_eq!(holder_count_result.return_value(), 2);
+            let total_supply = call_builder.total_supply();
+            let total_supply_result = client.call(&ink_e2e::alice(), &total_supply).dry_run().await?;
+            assert_eq!(total_supply_result.return_value(), 100);
+
+            // When - approve the rejecting spender
+            let approve_and_call = call_builder.approve_and_call(rejecting.account_id, 30, Vec::new());
+            let approve_result = client.call(&ink_e2e::alice(), &approve_and_call).dry_run().await?;
+
+            // Then - the approval is rejected
+            assert_eq!(approve_result.return_value(), Err(Error::CallbackFailed));
+
+            Ok(())
+        }
+
+        /// We test rescuing a second token sent to the contract by mistake.
+        #[ink_e2e::test]
+        async fn rescue_tokens_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let mut constructor = TokenRef::new();
+            let contract = client
+                .instantiate("token", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Token>();
+
+            let mut foreign_constructor = TokenRef::new();
+            let foreign = client
+                .instantiate("token", &ink_e2e::alice(), &mut foreign_constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut foreign_call_builder = foreign.call_builder::<Token>();
+
+            // Deposit foreign tokens at the contract's address
+            let mint = foreign_call_builder.mint(contract.account_id, 100);
+            let _mint_result = client
+                .call(&ink_e2e::alice(), &mint)
+                .submit()
+                .await
+                .expect("mint failed");
+
+            // When
+            let rescue = call_builder.rescue_tokens(foreign.account_id, ink_e2e::bob().account_id(), 60);
+            let _rescue_result = client
+                .call(&ink_e2e::alice(), &rescue)
+                .submit()
+                .await
+                .expect("rescue failed");
+
+            // Then
+            let balance_of = foreign_call_builder.balance_of(ink_e2e::bob().account_id());
+            let balance_result = client.call(&ink_e2e::alice(), &balance_of).dry_run().await?;
+            assert_eq!(balance_result.return_value(), 60);
+            let balance_of = foreign_call_builder.balance_of(contract.account_id);
+            let balance_result = client.call(&ink_e2e::alice(), &balance_of).dry_run().await?;
+            assert_eq!(balance_result.return_value(), 40);
+
+            // And - more than the contract holds can't be rescued
+            let rescue = call_builder.rescue_tokens(foreign.account_id, ink_e2e::bob().account_id(), 41);
+            let rescue_result = client.call(&ink_e2e::alice(), &rescue).dry_run().await?;
+            assert_eq!(rescue_result.return_value(), Err(Error::RescueFailed));
+
+            Ok(())
+        }
+
+        /// We test depositing native value and withdrawing it back.
+        #[ink_e2e::test]
+        async fn deposit_and_withdraw_work(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let mut constructor = TokenRef::new();
+            let contract = client
+                .instantiate("token", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Token>();
+            let native_before = client.free_balance(contract.account_id).await?;
+
+            // When - Bob wraps native value
+            let deposit = call_builder.deposit();
+            let _deposit_result = client
+                .call(&ink_e2e::bob(), &deposit)
+                .value(1_000_000)
+                .submit()
+                .await
+                .expect("deposit failed");
+
+            // Then - he holds as many tokens, backed by the contract's balance
+            let balance_of = call_builder.balance_of(ink_e2e::bob().account_id());
+            let balance_result = client.call(&ink_e2e::bob(), &balance_of).dry_run().await?;
+            assert_eq!(balance_result.return_value(), 1_000_000);
+            assert_eq!(client.free_balance(contract.account_id).await?, native_before + 1_000_000);
+
+            // When - he unwraps part of it
+            let withdraw = call_builder.withdraw(400_000);
+            let _withdraw_result = client
+                .call(&ink_e2e::bob(), &withdraw)
+                .submit()
+                .await
+                .expect("withdraw failed");
+
+            // Then - tokens and native balance went down together
+            let balance_result = client.call(&ink_e2e::bob(), &balance_of).dry_run().await?;
+            assert_eq!(balance_result.return_value(), 600_000);
+            let total_supply = call_builder.total_supply();
+            let supply_result = client.call(&ink_e2e::bob(), &total_supply).dry_run().await?;
+            assert_eq!(supply_result.return_value(), 600_000);
+            assert_eq!(client.free_balance(contract.account_id).await?, native_before + 600_000);
+
+            Ok(())
+        }
+
+        /// We test that the contract reports its account and the native value it holds.
+        #[ink_e2e::test]
+        async fn contract_account_and_balance_work(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let mut constructor = TokenRef::new();
+            let contract = client
+                .instantiate("token", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Token>();
+
+            let contract_account = call_builder.contract_account();
+            let account_result = client.call(&ink_e2e::bob(), &contract_account).dry_run().await?;
+            assert_eq!(account_result.return_value(), contract.account_id);
+            let contract_balance = call_builder.contract_balance();
+            let balance_before = client.call(&ink_e2e::bob(), &contract_balance).dry_run().await?.return_value();
+
+            // When - Bob wraps native value
+            let deposit = call_builder.deposit();
+            let _deposit_result = client
+                .call(&ink_e2e::bob(), &deposit)
+                .value(1_000_000)
+                .submit()
+                .await
+                .expect("deposit failed");
+
+            // Then - the reported balance grew by the deposit
+            let balance_after = client.call(&ink_e2e::bob(), &contract_balance).dry_run().await?.return_value();
+            assert_eq!(balance_after, balance_before + 1_000_000);
+
+            Ok(())
+        }
+
+        /// We test that upgrading the code switches behaviour but keeps balances and the owner gate.
+        #[ink_e2e::test]
+        async fn set_code_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let mut constructor = TokenRef::new();
+            let contract = client
+                .instantiate("token", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Token>();
+
+            let mint = call_builder.mint(ink_e2e::alice().account_id(), 100);
+            let _mint_result = client
+                .call(&ink_e2e::alice(), &mint)
+                .submit()
+                .await
+                .expect("mint failed");
+
+            // The current code has nothing to migrate
+            let migrate = call_builder.migrate();
+            let migrate_result = client.call(&ink_e2e::alice(), &migrate).dry_run().await?;
+            assert_eq!(migrate_result.return_value(), Err(Error::AlreadyMigrated));
+
+            // When - upload the next code version and switch to it
+            let new_code_hash = client
+                .upload("upgraded_token", &ink_e2e::alice())
+                .submit()
+                .await
+                .expect("upload failed")
+                .code_hash;
+            let set_code = call_builder.set_code(new_code_hash);
+            let not_owner_result = client.call(&ink_e2e::bob(), &set_code).dry_run().await?;
+            assert_eq!(not_owner_result.return_value(), Err(Error::NotOwner));
+            let _set_code_result = client
+                .call(&ink_e2e::alice(), &set_code)
+                .submit()
+                .await
+                .expect("set_code failed");
+
+            // Then - the new code runs against the old storage
+            let mut upgraded = contract.call_builder::<upgraded_token::upgraded_token::Token>();
+            let version = upgraded.version();
+            let version_result = client.call(&ink_e2e::alice(), &version).dry_run().await?;
+            assert_eq!(version_result.return_value(), STORAGE_VERSION);
+
+            let migrate = upgraded.migrate();
+            let _migrate_result = client
+                .call(&ink_e2e::alice(), &migrate)
+                .submit()
+                .await
+                .expect("migrate failed");
+            let version_result = client.call(&ink_e2e::alice(), &version).dry_run().await?;
+            assert_eq!(version_result.return_value(), upgraded_token::upgraded_token::STORAGE_VERSION);
+
+            // And - balances survive the upgrade
+            let balance_of = upgraded.balance_of(ink_e2e::alice().account_id());
+            let balance_result = client.call(&ink_e2e::alice(), &balance_of).dry_run().await?;
+            assert_eq!(balance_result.return_value(), 100);
+
+            Ok(())
+        }
     }
 }
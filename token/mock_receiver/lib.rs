@@ -0,0 +1,79 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// A minimal contract used by the token's end-to-end tests.
+/// It implements the `TokenReceiver::on_token_received` and
+/// `ApprovalReceiver::on_approval_received` hooks and either accepts or rejects
+/// every incoming transfer and approval. Accepted approvals are spent right away.
+#[ink::contract]
+pub mod mock_receiver {
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::prelude::string::String;
+    use ink::prelude::vec::Vec;
+
+    #[ink(storage)]
+    pub struct MockReceiver {
+        /// Whether incoming transfers are accepted
+        accept: bool,
+        /// Number of transfers accepted so far
+        received: u32,
+    }
+
+    impl MockReceiver {
+        /// Constructor that sets whether incoming transfers are accepted.
+        #[ink(constructor)]
+        pub fn new(accept: bool) -> Self {
+            Self { accept, received: 0 }
+        }
+
+        /// The `TokenReceiver::on_token_received` hook called by `safe_transfer`.
+        #[ink(message, selector = 0xe1936155)]
+        pub fn on_token_received(
+            &mut self,
+            _from: AccountId,
+            _amount: u128,
+            _data: Vec<u8>,
+        ) -> Result<(), String> {
+            if !self.accept {
+                return Err(String::from("rejected"));
+            }
+            self.received += 1;
+            Ok(())
+        }
+
+        /// The `ApprovalReceiver::on_approval_received` hook called by `approve_and_call`.
+        /// Pulls the whole allowance from `owner` into this contract.
+        #[ink(message, selector = 0xe3f70d43)]
+        pub fn on_approval_received(
+            &mut self,
+            owner: AccountId,
+            amount: u128,
+            _data: Vec<u8>,
+        ) -> Result<(), String> {
+            if !self.accept {
+                return Err(String::from("rejected"));
+            }
+            // Only success needs decoding; any `PSP22Error` fails to decode as `()`
+            let result = build_call::<Environment>()
+                .call(self.env().caller())
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer_from")))
+                        .push_arg(owner)
+                        .push_arg(self.env().account_id())
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), ()>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(String::from("transfer_from failed")),
+            }
+        }
+
+        /// Get the number of transfers accepted so far.
+        #[ink(message)]
+        pub fn received(&self) -> u32 {
+            self.received
+        }
+    }
+}
@@ -0,0 +1,125 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// A stand-in for the next release of the token, used by the token's `set_code`
+/// end-to-end test.
+/// Its storage struct is named `Token` like the original and lists the same
+/// packed fields in the same order, so it decodes the storage the token wrote.
+/// `Mapping` fields take no room in the packed struct and only `balances` is kept.
+/// Any field added to or reordered in the token must be mirrored here.
+#[ink::contract]
+pub mod upgraded_token {
+    use ink::prelude::string::String;
+    use ink::storage::Mapping;
+
+    /// Layout version this code brings storage up to.
+    pub const STORAGE_VERSION: u16 = 2;
+
+    /// The token storage as seen by the new code.
+    #[ink(storage)]
+    pub struct Token {
+        balances: Mapping<AccountId, u128>,
+        total_supply: u128,
+        name: String,
+        symbol: String,
+        decimals: u8,
+        owner: AccountId,
+        cap: u128,
+        paused: bool,
+        holder_count: u32,
+        snapshot_id: u32,
+        locked: bool,
+        fee_bps: u16,
+        fee_collector: AccountId,
+        merkle_root: [u8; 32],
+        allow_zero_address: bool,
+        total_minted: u128,
+        total_burned: u128,
+        max_balance: u128,
+        storage_version: u16,
+        reflection_bps: u16,
+        magnified_dividend_per_share: u128,
+        drip_amount: u128,
+        cooldown: u64,
+        signer: [u8; 32],
+        min_transfer: u128,
+        next_swap_id: u32,
+        pending_owner: Option<AccountId>,
+    }
+
+    /// Errors returned by the new code.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Caller is not the owner
+        NotOwner,
+        /// Storage is already at `STORAGE_VERSION`
+        AlreadyMigrated,
+    }
+
+    impl Default for Token {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Token {
+        /// Constructor that is never used, since the code is only reached through `set_code`.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                balances: Mapping::new(),
+                total_supply: 0,
+                name: String::new(),
+                symbol: String::new(),
+                decimals: 0,
+                owner: Self::env().caller(),
+                cap: u128::MAX,
+                paused: false,
+                holder_count: 0,
+                snapshot_id: 0,
+                locked: false,
+                fee_bps: 0,
+                fee_collector: Self::env().caller(),
+                merkle_root: [0u8; 32],
+                allow_zero_address: false,
+                total_minted: 0,
+                total_burned: 0,
+                max_balance: u128::MAX,
+                storage_version: STORAGE_VERSION,
+                reflection_bps: 0,
+                magnified_dividend_per_share: 0,
+                drip_amount: 0,
+                cooldown: 0,
+                signer: [0u8; 32],
+                min_transfer: 0,
+                next_swap_id: 0,
+                pending_owner: None,
+            }
+        }
+
+        /// The `PSP22::balance_of` message of the token.
+        #[ink(message, selector = 0x6568382f)]
+        pub fn balance_of(&self, owner: AccountId) -> u128 {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        /// Get the layout version of the data in storage.
+        #[ink(message)]
+        pub fn version(&self) -> u16 {
+            self.storage_version
+        }
+
+        /// Bring storage written by the token up to `STORAGE_VERSION`.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if self.storage_version >= STORAGE_VERSION {
+                return Err(Error::AlreadyMigrated);
+            }
+            self.storage_version = STORAGE_VERSION;
+            Ok(())
+        }
+    }
+}